@@ -0,0 +1,185 @@
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use bevy::{
+    input::{
+        gamepad::{Gamepad, GamepadAxis, GamepadButton},
+        ButtonInput,
+    },
+    log::{error, info, warn},
+    prelude::{DetectChanges, KeyCode, Res, ResMut, Resource},
+};
+use serde::{Deserialize, Serialize};
+
+use super::Action;
+
+static BINDINGS_PATH: &str = "config/bindings.json";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AxisDirection {
+    Positive,
+    Negative,
+}
+
+/// A physical input that can trigger an [`Action`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Binding {
+    Key(KeyCode),
+    GamepadButton(GamepadButton),
+    GamepadAxis {
+        axis: GamepadAxis,
+        direction: AxisDirection,
+    },
+}
+
+impl Binding {
+    /// How strongly this binding is held, from `0.` to `1.`. Digital inputs are either fully held or not at all,
+    /// axes are rescaled so that anything inside `deadzone` reads as `0.`.
+    pub fn value<'a>(
+        &self,
+        keyboard: &ButtonInput<KeyCode>,
+        gamepads: impl Iterator<Item = &'a Gamepad>,
+        deadzone: f32,
+    ) -> f32 {
+        let digital = |pressed: bool| if pressed { 1. } else { 0. };
+
+        match *self {
+            Self::Key(key) => digital(keyboard.pressed(key)),
+            Self::GamepadButton(button) => gamepads
+                .map(|gamepad| digital(gamepad.pressed(button)))
+                .fold(0., f32::max),
+            Self::GamepadAxis { axis, direction } => gamepads
+                .map(|gamepad| {
+                    let value = gamepad.get(axis).unwrap_or(0.);
+                    let value = match direction {
+                        AxisDirection::Positive => value,
+                        AxisDirection::Negative => -value,
+                    };
+                    ((value - deadzone) / (1. - deadzone)).clamp(0., 1.)
+                })
+                .fold(0., f32::max),
+        }
+    }
+}
+
+/// Maps every [`Action`] to the inputs that trigger it. Persisted to [`BINDINGS_PATH`] whenever it changes.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct InputBindings {
+    pub actions: BTreeMap<Action, Vec<Binding>>,
+    /// How far an axis has to be pushed before an axis binding counts as pressed.
+    pub axis_threshold: f32,
+    /// Stick travel that is ignored entirely, to stop worn sticks from drifting.
+    pub deadzone: f32,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self {
+            actions: BTreeMap::from([
+                (
+                    Action::MoveLeft,
+                    vec![
+                        Binding::Key(KeyCode::KeyA),
+                        Binding::GamepadButton(GamepadButton::DPadLeft),
+                        Binding::GamepadAxis {
+                            axis: GamepadAxis::LeftStickX,
+                            direction: AxisDirection::Negative,
+                        },
+                    ],
+                ),
+                (
+                    Action::MoveRight,
+                    vec![
+                        Binding::Key(KeyCode::KeyD),
+                        Binding::GamepadButton(GamepadButton::DPadRight),
+                        Binding::GamepadAxis {
+                            axis: GamepadAxis::LeftStickX,
+                            direction: AxisDirection::Positive,
+                        },
+                    ],
+                ),
+                (
+                    Action::Down,
+                    vec![
+                        Binding::Key(KeyCode::KeyS),
+                        Binding::GamepadButton(GamepadButton::DPadDown),
+                        Binding::GamepadAxis {
+                            axis: GamepadAxis::LeftStickY,
+                            direction: AxisDirection::Negative,
+                        },
+                    ],
+                ),
+                (
+                    Action::Sprint,
+                    vec![
+                        Binding::Key(KeyCode::ShiftLeft),
+                        Binding::Key(KeyCode::ShiftRight),
+                        Binding::GamepadButton(GamepadButton::RightTrigger2),
+                    ],
+                ),
+                (
+                    Action::Jump,
+                    vec![
+                        Binding::Key(KeyCode::Space),
+                        Binding::GamepadButton(GamepadButton::South),
+                    ],
+                ),
+            ]),
+            axis_threshold: 0.5,
+            deadzone: 0.15,
+        }
+    }
+}
+
+impl InputBindings {
+    pub fn iter(&self) -> impl Iterator<Item = (&Action, &Vec<Binding>)> {
+        self.actions.iter()
+    }
+
+    pub fn rebind(&mut self, action: Action, bindings: Vec<Binding>) {
+        self.actions.insert(action, bindings);
+    }
+
+    fn load(path: &Path) -> io::Result<Self> {
+        let contents = fs::read(path)?;
+        serde_json::from_slice(&contents).map_err(io::Error::from)
+    }
+
+    fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_vec_pretty(self)?)
+    }
+}
+
+pub(super) fn load_bindings(mut bindings: ResMut<InputBindings>) {
+    let path = Path::new(BINDINGS_PATH);
+
+    match InputBindings::load(path) {
+        Ok(mut loaded) => {
+            // actions added since the file was written keep their default bindings.
+            for (action, defaults) in InputBindings::default().actions {
+                loaded.actions.entry(action).or_insert(defaults);
+            }
+            *bindings = loaded;
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            info!("No input bindings at {BINDINGS_PATH}, writing defaults.");
+            if let Err(e) = bindings.save(path) {
+                error!("Could not write input bindings to {BINDINGS_PATH}: {e}");
+            }
+        }
+        Err(e) => warn!("Could not read input bindings from {BINDINGS_PATH}, using defaults: {e}"),
+    }
+}
+
+pub(super) fn save_bindings(bindings: Res<InputBindings>) {
+    if !bindings.is_changed() || bindings.is_added() {
+        return;
+    }
+
+    if let Err(e) = bindings.save(Path::new(BINDINGS_PATH)) {
+        error!("Could not write input bindings to {BINDINGS_PATH}: {e}");
+    }
+}
@@ -0,0 +1,61 @@
+pub use bindings::{AxisDirection, Binding, InputBindings};
+
+mod bindings;
+
+use bevy::{
+    app::{Plugin, PreUpdate, Startup, Update},
+    input::{gamepad::Gamepad, Axis, ButtonInput, InputSystem},
+    prelude::{IntoSystemConfigs, KeyCode, Query, Res, ResMut},
+};
+use bindings::{load_bindings, save_bindings};
+use serde::{Deserialize, Serialize};
+
+pub struct ActionPlugin;
+
+impl Plugin for ActionPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<ButtonInput<Action>>()
+            .init_resource::<Axis<Action>>()
+            .init_resource::<InputBindings>()
+            .add_systems(Startup, load_bindings)
+            .add_systems(PreUpdate, update_actions.after(InputSystem))
+            .add_systems(Update, save_bindings);
+    }
+}
+
+/// Something the player can do, independent of the physical input that triggers it.
+///
+/// Read [`ButtonInput<Action>`] for whether an action is held, or [`Axis<Action>`] for how strongly.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    /// Held together with [`Action::Jump`] to drop through one-way platforms.
+    Down,
+    Sprint,
+    Jump,
+}
+
+fn update_actions(
+    bindings: Res<InputBindings>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut actions: ResMut<ButtonInput<Action>>,
+    mut action_values: ResMut<Axis<Action>>,
+) {
+    actions.clear();
+
+    for (action, action_bindings) in bindings.iter() {
+        let value = action_bindings
+            .iter()
+            .map(|binding| binding.value(&keyboard, gamepads.iter(), bindings.deadzone))
+            .fold(0., f32::max);
+
+        action_values.set(*action, value);
+        if value > 0. && value >= bindings.axis_threshold {
+            actions.press(*action);
+        } else {
+            actions.release(*action);
+        }
+    }
+}
@@ -1,5 +1,17 @@
 #[macro_export]
 macro_rules! impl_intcell {
+    ($t:ty | $layer:literal: $id:expr; ($l:expr, $h:expr)) => {
+        impl $crate::world::IntCell for $t {
+            const DIMENSIONS: (f32, f32) = ($l, $h);
+            const INTCELL_ID: i32 = $id;
+            const LAYER: Option<&str> = Some($layer);
+        }
+    };
+
+    ($t:ty | $layer:literal: $id:expr; $l:expr) => {
+        impl_intcell!($t | $layer: $id; ($l, $l));
+    };
+
     ($t:ty | $id:expr; ($l:expr, $h:expr)) => {
         impl $crate::world::IntCell for $t {
             const DIMENSIONS: (f32, f32) = ($l, $h);
@@ -15,13 +27,13 @@ macro_rules! impl_intcell {
 #[macro_export]
 macro_rules! impl_entity {
     ($t:ty | $id:expr) => {
-        impl crate::world::Entity for $t {
+        impl $crate::world::Entity for $t {
             const IDENTIFIER: &str = $id;
         }
     };
 
     ($t:ty | $id:expr; ($l:expr; $h:expr)) => {
-        impl crate::world::Entity for $t {
+        impl $crate::world::Entity for $t {
             const IDENTIFIER: &str = $id;
             const DIMENSIONS: Option<(f32, f32)> = Some(($l, $h));
         }
@@ -72,3 +84,169 @@ macro_rules! sealed_trait {
         }
     };
 }
+
+/// Declares a level settings resource read from LDtk level fields. Every field becomes a newtype
+/// implementing `FromFieldInstances`, so adding a setting is a single line:
+///
+/// ```ignore
+/// level_settings! {
+///     #[derive(Resource, Default, Debug)]
+///     pub struct LevelSettings {
+///         is_dark: IsDark(bool) = "IsDark" => false,
+///         gravity_scale: GravityScale(Option<f32>) = "GravityScale",
+///     }
+/// }
+/// ```
+///
+/// `Option` fields are overrides and may be absent from the project. All others have to be declared in
+/// the project before being added here, or every level load reports them as missing.
+#[macro_export]
+macro_rules! level_settings {
+    (@default) => { Default::default() };
+    (@default $default:expr) => { $default };
+
+    (
+        $(#[$meta:meta])*
+        $v:vis struct $name:ident {
+            $(
+                $(#[$field_meta:meta])*
+                $field:ident: $newtype:ident($inner:ty) = $id:literal $(=> $default:expr)?
+            ),* $(,)?
+        }
+    ) => {
+        $(
+            $(#[$field_meta])*
+            #[derive(Debug, Clone, PartialEq, ::derive_more::derive::Deref)]
+            pub struct $newtype(pub $inner);
+
+            impl Default for $newtype {
+                fn default() -> Self {
+                    Self($crate::level_settings!(@default $($default)?))
+                }
+            }
+
+            impl $crate::world::level_settings::FromFieldValue for $newtype {
+                const FIELD_TYPE: &'static str =
+                    <$inner as $crate::world::level_settings::FromFieldValue>::FIELD_TYPE;
+                const NULLABLE: bool =
+                    <$inner as $crate::world::level_settings::FromFieldValue>::NULLABLE;
+
+                fn from_field_value(
+                    val: ::bevy_ecs_ldtk::ldtk::FieldValue,
+                ) -> Result<Self, $crate::world::level_settings::UnexpectedFieldValue> {
+                    <$inner as $crate::world::level_settings::FromFieldValue>::from_field_value(val)
+                        .map(Self)
+                }
+            }
+
+            impl $crate::world::level_settings::FromFieldInstances for $newtype {
+                const IDENTIFIER: &'static str = $id;
+            }
+        )*
+
+        $(#[$meta])*
+        $v struct $name {
+            $(
+                $(#[$field_meta])*
+                pub $field: $newtype,
+            )*
+        }
+
+        impl $name {
+            pub const FIELDS: &'static [$crate::world::level_settings::LevelFieldInfo] = &[
+                $(<$newtype as $crate::world::level_settings::FromFieldInstances>::INFO),*
+            ];
+
+            /// Reads every setting from the level's fields. Fields that can't be read keep their default and
+            /// are returned alongside.
+            pub fn from_field_instances(
+                fi: &[::bevy_ecs_ldtk::ldtk::FieldInstance],
+            ) -> (Self, Vec<$crate::world::level_settings::LevelFieldError>) {
+                let mut errors = Vec::new();
+                let settings = Self {
+                    $(
+                        $field: <$newtype as $crate::world::level_settings::FromFieldInstances>
+                            ::from_field_instances_or_default(fi, &mut errors),
+                    )*
+                };
+                (settings, errors)
+            }
+        }
+    };
+}
+
+/// Declares an enum read from an LDtk `LocalEnum` field of the same name, usable in [`level_settings!`].
+/// Variants must be named exactly like their LDtk counterparts.
+#[macro_export]
+macro_rules! level_enum {
+    (
+        $(#[$meta:meta])*
+        $v:vis enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        $v enum $name {
+            $($(#[$variant_meta])* $variant),*
+        }
+
+        impl $name {
+            fn from_ldtk_name(name: &str) -> Option<Self> {
+                match name {
+                    $(stringify!($variant) => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+
+        impl $crate::world::level_settings::FromFieldValue for $name {
+            const FIELD_TYPE: &'static str = concat!("LocalEnum.", stringify!($name));
+
+            fn from_field_value(
+                val: ::bevy_ecs_ldtk::ldtk::FieldValue,
+            ) -> Result<Self, $crate::world::level_settings::UnexpectedFieldValue> {
+                match &val {
+                    ::bevy_ecs_ldtk::ldtk::FieldValue::Enum(Some(name)) => Self::from_ldtk_name(name),
+                    _ => None,
+                }
+                .ok_or($crate::world::level_settings::UnexpectedFieldValue(val))
+            }
+        }
+
+        impl $crate::world::level_settings::FromFieldValue for Option<$name> {
+            const FIELD_TYPE: &'static str = concat!("LocalEnum.", stringify!($name));
+            const NULLABLE: bool = true;
+
+            fn from_field_value(
+                val: ::bevy_ecs_ldtk::ldtk::FieldValue,
+            ) -> Result<Self, $crate::world::level_settings::UnexpectedFieldValue> {
+                match &val {
+                    ::bevy_ecs_ldtk::ldtk::FieldValue::Enum(None) => Some(None),
+                    ::bevy_ecs_ldtk::ldtk::FieldValue::Enum(Some(name)) => {
+                        $name::from_ldtk_name(name).map(Some)
+                    }
+                    _ => None,
+                }
+                .ok_or($crate::world::level_settings::UnexpectedFieldValue(val))
+            }
+        }
+
+        impl $crate::world::level_settings::FromFieldValue for Vec<$name> {
+            const FIELD_TYPE: &'static str = concat!("Array<LocalEnum.", stringify!($name), ">");
+
+            fn from_field_value(
+                val: ::bevy_ecs_ldtk::ldtk::FieldValue,
+            ) -> Result<Self, $crate::world::level_settings::UnexpectedFieldValue> {
+                match &val {
+                    ::bevy_ecs_ldtk::ldtk::FieldValue::Enums(names) => names
+                        .iter()
+                        .map(|name| name.as_deref().and_then($name::from_ldtk_name))
+                        .collect(),
+                    _ => None,
+                }
+                .ok_or($crate::world::level_settings::UnexpectedFieldValue(val))
+            }
+        }
+    };
+}
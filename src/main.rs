@@ -1,6 +1,7 @@
 #![allow(unused_parens)]
 
 pub mod components;
+pub mod input;
 pub mod macros;
 pub mod player;
 pub mod render;
@@ -18,6 +19,7 @@ use bevy_ecs_ldtk::LdtkPlugin;
 use bevy_tnua::prelude::TnuaControllerPlugin;
 use bevy_tnua_avian2d::TnuaAvian2dPlugin;
 use components::ComponentPlugin;
+use input::ActionPlugin;
 use player::PlayerPlugin;
 use render::RenderPlugins;
 use world::WorldPlugins;
@@ -39,6 +41,7 @@ fn main() {
                 }),
         )
         .add_plugins(LdtkPlugin)
+        .add_plugins(ActionPlugin)
         .add_plugins(RenderPlugins)
         .add_plugins(WorldPlugins)
         .add_plugins(PlayerPlugin)
@@ -1,19 +1,33 @@
 use avian2d::prelude::{Collider, RigidBody};
 use bevy::{
-    prelude::{Bundle, Component},
+    prelude::{Bundle, Component, Entity},
     sprite::Sprite,
-    utils::default,
+    utils::{default, HashSet},
+};
+use bevy_ecs_ldtk::{app::LdtkEntity, GridCoords, Worldly};
+use bevy_tnua::{
+    control_helpers::TnuaSimpleFallThroughPlatformsHelper, prelude::TnuaController,
+    TnuaAnimatingState, TnuaGhostSensor,
 };
-use bevy_ecs_ldtk::{app::LdtkEntity, GridCoords};
-use bevy_tnua::{prelude::TnuaController, TnuaAnimatingState};
 
-use crate::{components::EntityDirection, render::animation::AnimationConfig};
+use crate::{
+    components::EntityDirection, impl_entity, render::animation::AnimationConfig,
+    world::platform::PassThroughOneWayPlatform,
+};
 
 pub const PLAYER_DIM: (f32, f32) = (16., 28.);
 
 #[derive(Component, Default)]
 pub struct Player;
 
+/// Seconds left during which a jump press that hasn't turned into a jump yet is still honoured.
+#[derive(Component, Default)]
+pub(super) struct JumpBuffer(pub f32);
+
+/// The one-way platforms the player is dropping through, empty when not dropping.
+#[derive(Component, Default)]
+pub(super) struct DropThrough(pub HashSet<Entity>);
+
 #[derive(Bundle)]
 pub(super) struct PlayerBundle {
     player: Player,
@@ -25,8 +39,17 @@ pub(super) struct PlayerBundle {
     animation_config: AnimationConfig,
     animating_state: TnuaAnimatingState<PlayerState>,
     direction: EntityDirection,
+    jump_buffer: JumpBuffer,
+    drop_through: DropThrough,
+    ghost_sensor: TnuaGhostSensor,
+    fall_through_helper: TnuaSimpleFallThroughPlatformsHelper,
+    pass_through: PassThroughOneWayPlatform,
+    // the player persists across levels and gets moved to the right spawn point instead.
+    worldly: Worldly,
 }
 
+impl_entity!(PlayerBundle | "Player");
+
 impl LdtkEntity for PlayerBundle {
     fn bundle_entity(
         entity_instance: &bevy_ecs_ldtk::EntityInstance,
@@ -55,6 +78,12 @@ impl LdtkEntity for PlayerBundle {
             animating_state: default(),
             tnua_controller: default(),
             direction: default(),
+            jump_buffer: default(),
+            drop_through: default(),
+            ghost_sensor: default(),
+            fall_through_helper: default(),
+            pass_through: default(),
+            worldly: Worldly::from_entity_info(entity_instance),
         }
     }
 }
@@ -0,0 +1,75 @@
+use bevy::{
+    asset::{Asset, AssetEvent, AssetServer, Assets, Handle},
+    prelude::{Commands, EventReader, Res, ResMut, Resource},
+    reflect::TypePath,
+};
+use serde::Deserialize;
+
+use super::component::PLAYER_DIM;
+
+pub(super) static MOVEMENT_CONFIG_PATH: &str = "config/player.movement.json";
+
+/// Movement tuning for the player, loaded from [`MOVEMENT_CONFIG_PATH`] and hot-reloaded on change.
+#[derive(Asset, TypePath, Resource, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct PlayerMovementConfig {
+    pub movement_factor: f32,
+    pub sprint_factor: f32,
+    pub acceleration: f32,
+    pub jump_height: f32,
+    /// Extra gravity applied once jump is released early, cutting the jump short.
+    pub jump_cut_gravity: f32,
+    /// Seconds after walking off a ledge during which a jump is still allowed.
+    pub coyote_time: f32,
+    /// Seconds a jump press is remembered before landing.
+    pub jump_buffer_time: f32,
+    pub float_height: f32,
+    /// One-way platforms closer than this below the float ray's origin are ones the player is already inside of.
+    pub one_way_min_proximity: f32,
+    pub running_min: f32,
+    pub walking_min: f32,
+}
+
+impl Default for PlayerMovementConfig {
+    fn default() -> Self {
+        Self {
+            movement_factor: 50.,
+            sprint_factor: 3.,
+            acceleration: 50.,
+            jump_height: 24.,
+            jump_cut_gravity: 60.,
+            coyote_time: 0.15,
+            jump_buffer_time: 0.15,
+            float_height: (PLAYER_DIM.1 / 2.) + 0.3,
+            one_way_min_proximity: 1.,
+            running_min: 80.,
+            walking_min: 0.1,
+        }
+    }
+}
+
+// keeps the asset alive so that modifications keep being picked up.
+#[derive(Resource)]
+pub(super) struct PlayerMovementConfigHandle(#[allow(dead_code)] Handle<PlayerMovementConfig>);
+
+pub(super) fn load_movement_config(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(PlayerMovementConfigHandle(
+        asset_server.load(MOVEMENT_CONFIG_PATH),
+    ));
+}
+
+pub(super) fn apply_movement_config(
+    mut events: EventReader<AssetEvent<PlayerMovementConfig>>,
+    assets: Res<Assets<PlayerMovementConfig>>,
+    mut config: ResMut<PlayerMovementConfig>,
+) {
+    for event in events.read() {
+        let (AssetEvent::Added { id } | AssetEvent::Modified { id }) = event else {
+            continue;
+        };
+        let Some(new_config) = assets.get(*id) else {
+            continue;
+        };
+        *config = new_config.clone();
+    }
+}
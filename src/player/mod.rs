@@ -1,20 +1,28 @@
 pub use component::{Player, PLAYER_DIM};
+pub use config::PlayerMovementConfig;
 pub use movement::SyncCameraWithPlayer;
 
 mod component;
+mod config;
 mod movement;
 
 use bevy::{
-    app::{Plugin, Update},
-    prelude::{IntoSystemConfigs, Resource},
+    app::{Plugin, PostUpdate, Startup, Update},
+    asset::AssetApp,
+    prelude::{in_state, IntoSystemConfigs, OnEnter, Resource},
 };
-use bevy_ecs_ldtk::app::LdtkEntityAppExt;
 use component::PlayerBundle;
-use movement::{animate_player, camera_follow_player, move_player, sync_camera_with_player};
-
-use crate::render::animation::AnimationConfig;
+use config::{apply_movement_config, load_movement_config};
+use movement::{
+    animate_player, camera_follow_player, move_player, shake_on_hard_landing, stop_player,
+    sync_camera_with_player,
+};
 
-static PLAYER_ID: &'static str = "Player";
+use crate::{
+    render::{animation::AnimationConfig, camera::CameraSystems},
+    utils::{JsonAssetLoader, LdtkAppTraitExt},
+    world::transition::LevelTransition,
+};
 
 pub struct PlayerPlugin;
 
@@ -23,13 +31,31 @@ impl Plugin for PlayerPlugin {
         app.add_systems(
             Update,
             (
-                sync_camera_with_player,
-                (move_player, animate_player, camera_follow_player).chain(),
+                shake_on_hard_landing,
+                (
+                    apply_movement_config,
+                    move_player.run_if(in_state(LevelTransition::Playing)),
+                    animate_player,
+                    camera_follow_player,
+                )
+                    .chain(),
             ),
         )
+        .add_systems(
+            PostUpdate,
+            // once the level change or respawn asking for it has placed the player.
+            sync_camera_with_player.in_set(CameraSystems::Place),
+        )
+        .add_systems(Startup, load_movement_config)
+        .add_systems(OnEnter(LevelTransition::FadingOut), stop_player)
         .add_event::<SyncCameraWithPlayer>()
+        .init_asset::<PlayerMovementConfig>()
+        .register_asset_loader(JsonAssetLoader::<PlayerMovementConfig>::new(&[
+            "movement.json",
+        ]))
+        .init_resource::<PlayerMovementConfig>()
         .init_resource::<PlayerAnimationPresets>()
-        .register_ldtk_entity::<PlayerBundle>(&PLAYER_ID);
+        .register_ldtk_entity::<PlayerBundle>();
     }
 }
 
@@ -1,124 +1,286 @@
+use avian2d::prelude::LinearVelocity;
 use bevy::{
-    input::ButtonInput,
+    input::{Axis, ButtonInput},
     log::{error, warn},
-    math::{Dir3, Vec3},
+    math::{Dir3, Vec2, Vec3},
     prelude::{
-        Camera2d, Event, EventReader, GlobalTransform, KeyCode, Query, Res, Transform, With,
-        Without,
+        Camera2d, Event, EventReader, EventWriter, GlobalTransform, Local, Query, Res, Transform,
+        With, Without,
     },
+    time::Time,
     utils::default,
 };
 use bevy_tnua::{
+    control_helpers::TnuaSimpleFallThroughPlatformsHelper,
     prelude::{TnuaBuiltinJump, TnuaBuiltinWalk, TnuaController},
-    TnuaAnimatingState,
+    TnuaAction, TnuaAnimatingState, TnuaGhostSensor, TnuaProximitySensor,
 };
 
 use crate::{
-    components::EntityDirection, render::animation::AnimationConfig,
-    world::level_settings::LevelSettings,
+    components::EntityDirection,
+    input::Action,
+    render::{
+        animation::AnimationConfig,
+        camera::{CameraController, CameraTrauma},
+    },
+    world::{
+        camera_zone::{ActiveCameraZone, CameraZone, CameraZoneMode},
+        level_settings::LevelSettings,
+        platform::PassThroughOneWayPlatform,
+    },
 };
 
 use super::{
-    component::{Player, PlayerState, PLAYER_DIM},
+    component::{DropThrough, JumpBuffer, Player, PlayerState},
+    config::PlayerMovementConfig,
     PlayerAnimationPresets,
 };
 
-const MOVEMENT_FACTOR: f32 = 50.;
-const SPRINT_FACTOR: f32 = 3.;
-const FLOAT_HEIGHT: f32 = (PLAYER_DIM.1 / 2.) + 0.3;
-const ACCELERATION: f32 = 50.;
-const JUMP_HEIGHT: f32 = 24.;
-const RUNNING_MIN: f32 = 80.;
-const WALKING_MIN: f32 = 0.1;
-
+#[allow(clippy::type_complexity)]
 pub(super) fn move_player(
-    mut controller: Query<(&mut TnuaController, &mut EntityDirection, &Transform), With<Player>>,
-    keyboard: Res<ButtonInput<KeyCode>>,
+    mut controller: Query<
+        (
+            &mut TnuaController,
+            &mut JumpBuffer,
+            &mut EntityDirection,
+            &Transform,
+            &mut TnuaProximitySensor,
+            &TnuaGhostSensor,
+            &mut TnuaSimpleFallThroughPlatformsHelper,
+            &mut DropThrough,
+            &mut PassThroughOneWayPlatform,
+        ),
+        With<Player>,
+    >,
+    actions: Res<ButtonInput<Action>>,
+    action_values: Res<Axis<Action>>,
+    config: Res<PlayerMovementConfig>,
+    level_settings: Res<LevelSettings>,
+    time: Res<Time>,
 ) {
-    let Ok((mut controller, mut direction, transform)) = controller.get_single_mut() else {
+    let Ok((
+        mut controller,
+        mut jump_buffer,
+        mut direction,
+        transform,
+        mut proximity_sensor,
+        ghost_sensor,
+        mut fall_through_helper,
+        mut drop_through,
+        mut pass_through,
+    )) = controller.get_single_mut()
+    else {
         return;
     };
 
     let mut direction_v = Vec3::splat(0.);
 
-    if keyboard.pressed(KeyCode::KeyD) {
-        direction_v.x = 1.;
+    // analog, so a half-tilted stick walks at half speed.
+    direction_v.x = action_values.get(Action::MoveRight).unwrap_or(0.)
+        - action_values.get(Action::MoveLeft).unwrap_or(0.);
+
+    if direction_v.x > 0. {
         *direction = EntityDirection::R;
-    } else if keyboard.pressed(KeyCode::KeyA) {
-        direction_v.x = -1.;
+    } else if direction_v.x < 0. {
         *direction = EntityDirection::L;
     }
 
-    if keyboard.pressed(KeyCode::ShiftLeft) || keyboard.pressed(KeyCode::ShiftRight) {
-        direction_v *= SPRINT_FACTOR;
+    if actions.pressed(Action::Sprint) {
+        direction_v *= config.sprint_factor;
     }
 
-    direction_v *= MOVEMENT_FACTOR;
+    direction_v *= config.movement_factor * level_settings.speed_multiplier.unwrap_or(1.);
     direction_v.z = transform.translation.z;
 
     controller.basis(TnuaBuiltinWalk {
         desired_velocity: direction_v,
         desired_forward: Dir3::new(direction_v).ok(),
-        float_height: FLOAT_HEIGHT,
-        acceleration: ACCELERATION,
+        float_height: config.float_height,
+        acceleration: config.acceleration * level_settings.acceleration_multiplier.unwrap_or(1.),
+        coyote_time: level_settings.coyote_time.unwrap_or(config.coyote_time),
         ..default()
     });
 
-    if !keyboard.pressed(KeyCode::Space) {
+    let start_drop = actions.pressed(Action::Down) && actions.just_pressed(Action::Jump);
+    if start_drop {
+        drop_through.0 = ghost_sensor
+            .iter()
+            .filter(|hit| hit.proximity >= config.one_way_min_proximity)
+            .map(|hit| hit.entity)
+            .collect();
+    } else {
+        // done once the platforms are no longer below the float ray's origin.
+        drop_through
+            .0
+            .retain(|platform| ghost_sensor.iter().any(|hit| hit.entity == *platform));
+    }
+
+    let mut fall_through = fall_through_helper.with(
+        &mut proximity_sensor,
+        ghost_sensor,
+        config.one_way_min_proximity,
+    );
+    let dropping = !drop_through.0.is_empty();
+    if dropping {
+        fall_through.try_falling(start_drop);
+    } else {
+        fall_through.dont_fall();
+    }
+
+    // the collider has to be let through as well, tnua only handles the float ray.
+    let new_pass_through = if dropping {
+        PassThroughOneWayPlatform::Always
+    } else {
+        PassThroughOneWayPlatform::ByNormal
+    };
+    if *pass_through != new_pass_through {
+        *pass_through = new_pass_through;
+    }
+
+    // down + jump is only a jump when there's nothing to drop through.
+    if start_drop && dropping {
+        jump_buffer.0 = 0.;
+        return;
+    }
+
+    if actions.just_pressed(Action::Jump) {
+        jump_buffer.0 = level_settings
+            .jump_buffer_time
+            .unwrap_or(config.jump_buffer_time);
+    } else {
+        jump_buffer.0 = (jump_buffer.0 - time.delta_secs()).max(0.);
+    }
+
+    // the buffered press has been used up once the jump actually starts.
+    if controller.action_name() == Some(TnuaBuiltinJump::NAME) {
+        jump_buffer.0 = 0.;
+    }
+
+    // Tnua cuts the jump short as soon as we stop feeding it.
+    if !actions.pressed(Action::Jump) && jump_buffer.0 <= 0. {
         return;
     }
 
     controller.action(TnuaBuiltinJump {
-        height: JUMP_HEIGHT,
+        height: level_settings.jump_height.unwrap_or(config.jump_height),
+        shorten_extra_gravity: config.jump_cut_gravity,
         ..default()
     });
 }
 
-pub(super) fn camera_follow_player(
-    mut camera: Query<&mut Transform, (With<Camera2d>, Without<Player>)>,
-    player: Query<&GlobalTransform, (With<Player>, Without<Camera2d>)>,
-    level_settings: Res<LevelSettings>,
+// input is ignored during level transitions, without this the player would keep walking.
+pub(super) fn stop_player(
+    mut controller: Query<&mut TnuaController, With<Player>>,
+    config: Res<PlayerMovementConfig>,
 ) {
-    if *level_settings.camera_follow == false {
-        // if the level asks us for the camera to stay put, it'll do so.
-        return;
+    for mut controller in &mut controller {
+        controller.basis(TnuaBuiltinWalk {
+            float_height: config.float_height,
+            ..default()
+        });
     }
+}
 
-    let Ok(mut camera) = camera.get_single_mut() else {
+#[allow(clippy::type_complexity)]
+pub(super) fn camera_follow_player(
+    mut camera: Query<(&mut Transform, &mut CameraController), (With<Camera2d>, Without<Player>)>,
+    player: Query<
+        (&GlobalTransform, &EntityDirection, &LinearVelocity),
+        (With<Player>, Without<Camera2d>),
+    >,
+    zones: Query<(&CameraZone, &GlobalTransform)>,
+    active_zone: Res<ActiveCameraZone>,
+    level_settings: Res<LevelSettings>,
+    time: Res<Time>,
+) {
+    let Ok((mut camera, mut controller)) = camera.get_single_mut() else {
         error!("Camera should exist at this point!");
         return;
     };
-    let Ok(player) = player.get_single() else {
+    let Ok((player, direction, velocity)) = player.get_single() else {
         warn!("Expected player, found none.");
         return;
     };
 
-    camera.translation.x = player.translation().x;
-    camera.translation.y = player.translation().y;
+    let facing = match direction {
+        EntityDirection::L => -1.,
+        EntityDirection::R => 1.,
+    };
+    let (target, facing, velocity) = match active_zone.0.and_then(|zone| zones.get(zone).ok()) {
+        // a locked zone holds the camera on itself, without looking anywhere.
+        Some((zone, transform)) if zone.mode == CameraZoneMode::Lock => {
+            (transform.translation().truncate(), 0., Vec2::ZERO)
+        }
+        _ => (player.translation().truncate(), facing, velocity.0),
+    };
+    let position = controller.follow(
+        camera.translation.truncate(),
+        target,
+        facing,
+        velocity,
+        time.delta_secs(),
+        &level_settings,
+    );
+
+    camera.translation.x = position.x;
+    camera.translation.y = position.y;
 }
 
+/// Falling speed the player has to land at for the camera to shake.
+const HARD_LANDING_SPEED: f32 = 150.;
+
+pub(super) fn shake_on_hard_landing(
+    player: Query<(&TnuaController, &LinearVelocity), With<Player>>,
+    mut fall_speed: Local<f32>,
+    mut trauma: EventWriter<CameraTrauma>,
+) {
+    let Ok((controller, velocity)) = player.get_single() else {
+        return;
+    };
+
+    if controller.is_airborne().unwrap_or(false) {
+        // the velocity is already gone on the frame the player lands, so keep the fastest one.
+        *fall_speed = fall_speed.max(-velocity.y);
+        return;
+    }
+
+    if *fall_speed >= HARD_LANDING_SPEED {
+        let hardness = (*fall_speed / HARD_LANDING_SPEED - 1.).min(1.);
+        trauma.send(CameraTrauma(0.3 + 0.3 * hardness));
+    }
+    *fall_speed = 0.;
+}
+
+/// Snaps the camera onto the player, as soon as both exist and once the player has been placed for
+/// the frame.
 #[derive(Event, Default)]
 pub struct SyncCameraWithPlayer;
 
 // For level changes
+#[allow(clippy::type_complexity)]
 pub(super) fn sync_camera_with_player(
     mut event_reader: EventReader<SyncCameraWithPlayer>,
-    mut camera: Query<&mut Transform, (With<Camera2d>, Without<Player>)>,
+    mut pending: Local<bool>,
+    mut camera: Query<(&mut Transform, &mut CameraController), (With<Camera2d>, Without<Player>)>,
     player: Query<&Transform, (With<Player>, Without<Camera2d>)>,
 ) {
-    let Ok(mut camera) = camera.get_single_mut() else {
-        error!("Camera instance should exist at this point!");
+    // a new level's player may not exist yet, so the request is kept until it does.
+    *pending |= event_reader.read().count() > 0;
+    if !*pending {
+        return;
+    }
+
+    let Ok((mut camera, mut controller)) = camera.get_single_mut() else {
         return;
     };
     let Ok(player) = player.get_single() else {
-        warn!("Expected player, found none.");
         return;
     };
 
-    for _ in event_reader.read() {
-        camera.translation.x = player.translation.x;
-        camera.translation.y = player.translation.y;
-    }
+    camera.translation.x = player.translation.x;
+    camera.translation.y = player.translation.y;
+    controller.reset();
+    *pending = false;
 }
 
 pub(super) fn animate_player(
@@ -128,7 +290,12 @@ pub(super) fn animate_player(
         &TnuaController,
     )>,
     animation_presets: Res<PlayerAnimationPresets>,
+    movement_config: Res<PlayerMovementConfig>,
+    level_settings: Res<LevelSettings>,
 ) {
+    // a slower level shouldn't keep the player from ever looking like they're running.
+    let running_min = movement_config.running_min * level_settings.speed_multiplier.unwrap_or(1.);
+
     for (mut animating_state, mut config, controller) in &mut query {
         match animating_state.update_by_discriminant({
             let Some((_, basis_state)) = controller.concrete_basis::<TnuaBuiltinWalk>() else {
@@ -136,9 +303,9 @@ pub(super) fn animate_player(
             };
 
             let speed = basis_state.running_velocity.length();
-            if speed < WALKING_MIN {
+            if speed < movement_config.walking_min {
                 PlayerState::Idle
-            } else if speed < RUNNING_MIN {
+            } else if speed < running_min {
                 PlayerState::Walking
             } else {
                 PlayerState::Running
@@ -165,3 +332,84 @@ pub(super) fn animate_player(
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use avian2d::prelude::LinearVelocity;
+    use bevy::{
+        app::{App, PostUpdate, Update},
+        math::Vec3,
+        prelude::{Camera2d, Transform, With},
+    };
+    use bevy_ecs_ldtk::{LevelEvent, LevelSelection};
+
+    use super::{sync_camera_with_player, SyncCameraWithPlayer};
+    use crate::{
+        player::Player,
+        render::camera::CameraController,
+        world::{
+            death::RespawnPoint,
+            level_settings::{LevelSettings, Spawnpoint},
+            spawn::{
+                spawn_player,
+                tests::{app_with_levels, level_iid},
+                PendingSpawn,
+            },
+        },
+    };
+
+    #[test]
+    fn camera_lands_on_player_after_level_change() {
+        let mut app = app_with_levels();
+        app.add_event::<LevelEvent>()
+            .add_event::<SyncCameraWithPlayer>()
+            .insert_resource(LevelSelection::Identifier("Level_1".into()))
+            .insert_resource(LevelSettings {
+                spawnpoint: Spawnpoint(Some("2;3".into())),
+                ..Default::default()
+            })
+            .init_resource::<RespawnPoint>()
+            .add_systems(Update, spawn_player)
+            .add_systems(PostUpdate, sync_camera_with_player);
+        app.world_mut()
+            .spawn((Camera2d, CameraController::default(), Transform::default()));
+        // still where the previous level left them.
+        let player = app
+            .world_mut()
+            .spawn((
+                Player,
+                Transform::from_xyz(-300., 80., 0.),
+                LinearVelocity::default(),
+            ))
+            .id();
+
+        let camera_translation = |app: &mut App| {
+            let mut cameras = app
+                .world_mut()
+                .query_filtered::<&Transform, With<CameraController>>();
+            cameras.single(app.world()).translation
+        };
+
+        // the level change has selected the new level, which is still spawning.
+        app.insert_resource(PendingSpawn::Default);
+        for _ in 0..3 {
+            app.update();
+            assert_eq!(camera_translation(&mut app), Vec3::ZERO);
+        }
+
+        app.world_mut()
+            .send_event(LevelEvent::Transformed(level_iid(1)));
+        app.update();
+        let player_translation = app.world().get::<Transform>(player).unwrap().translation;
+        assert_eq!(player_translation.x, 190. + 2.5 * 19.);
+        assert_eq!(camera_translation(&mut app), player_translation);
+
+        // and only once, the camera is free to follow afterwards.
+        let mut cameras = app
+            .world_mut()
+            .query_filtered::<&mut Transform, With<CameraController>>();
+        cameras.single_mut(app.world_mut()).translation = Vec3::ZERO;
+        app.update();
+        assert_eq!(camera_translation(&mut app), Vec3::ZERO);
+    }
+}
@@ -1,20 +1,359 @@
 use bevy::{
-    app::{Plugin, Startup},
-    math::Vec3,
-    prelude::{Camera2d, Commands, Transform},
+    app::{Plugin, PostUpdate, Startup, Update},
+    asset::Assets,
+    math::{IVec2, Quat, Rect, Vec2},
+    prelude::{
+        Camera, Camera2d, Commands, Component, Entity, Event, EventReader, GlobalTransform,
+        IntoSystemConfigs, IntoSystemSetConfigs, Local, Msaa, OrthographicProjection, Query, Res,
+        Single, SystemSet, Transform, TransformSystem, With,
+    },
+    render::camera::RenderTarget,
+    time::Time,
+    utils::default,
 };
+use bevy_ecs_ldtk::{assets::LdtkProject, LdtkProjectHandle, LevelIid, LevelSelection};
 
-const CAMERA_SCALE: f32 = 1. / 5.;
+use super::pixel::PixelCanvas;
+use crate::world::{
+    camera_zone::{ActiveCameraZone, CameraZone, CameraZoneMode},
+    death::DeathSequence,
+    level::{level_bounds, selected_level_entity},
+    level_settings::LevelSettings,
+};
+
+/// Trauma added when the player dies.
+const DEATH_TRAUMA: f32 = 0.6;
 
 #[derive(Default)]
 pub(super) struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_systems(Startup, spawn_camera);
+        app.add_event::<CameraTrauma>()
+            .configure_sets(
+                PostUpdate,
+                // after everything that moves the camera during `Update`.
+                (CameraSystems::Place, CameraSystems::Clamp)
+                    .chain()
+                    .before(TransformSystem::TransformPropagate),
+            )
+            .add_systems(Startup, spawn_camera)
+            .add_systems(Update, (add_camera_trauma, zoom_camera))
+            .add_systems(
+                PostUpdate,
+                (
+                    clamp_camera_to_level.in_set(CameraSystems::Clamp),
+                    // only shakes what's rendered, so the shake never drifts the camera.
+                    shake_camera.after(TransformSystem::TransformPropagate),
+                ),
+            );
     }
 }
 
-fn spawn_camera(mut commands: Commands) {
-    commands.spawn((Camera2d, Transform::from_scale(Vec3::splat(CAMERA_SCALE))));
+/// Where the camera is settled in `PostUpdate`, once everything else has moved for the frame.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CameraSystems {
+    /// Systems putting the camera somewhere outright, e.g. onto the player after a level change.
+    Place,
+    /// Keeps the camera inside the level, or the camera zone the player is in.
+    Clamp,
+}
+
+/// How the camera follows its target. Every parameter can be overridden per level through
+/// [`LevelSettings`].
+#[derive(Component, Debug, Clone)]
+pub struct CameraController {
+    /// How quickly the camera catches up, higher is snappier.
+    pub smoothing: f32,
+    /// Half extents of the box around the camera's centre the target moves freely in.
+    pub deadzone: Vec2,
+    /// How far ahead of the target the camera looks, in the direction it faces.
+    pub look_ahead: f32,
+    /// How far below the target the camera looks while it falls.
+    pub fall_bias: f32,
+    /// Downward speed past which the target counts as falling.
+    pub fall_speed: f32,
+    // the current offsets, eased towards so that turning around doesn't jerk the view.
+    look_ahead_offset: f32,
+    fall_offset: f32,
+}
+
+impl Default for CameraController {
+    fn default() -> Self {
+        Self {
+            smoothing: 6.,
+            deadzone: Vec2::new(8., 12.),
+            look_ahead: 24.,
+            fall_bias: 20.,
+            fall_speed: 60.,
+            look_ahead_offset: 0.,
+            fall_offset: 0.,
+        }
+    }
+}
+
+impl CameraController {
+    /// Moves `camera` a step of `delta` seconds towards `target`, which faces `facing` (`-1.` or
+    /// `1.`) and moves at `velocity`.
+    pub fn follow(
+        &mut self,
+        camera: Vec2,
+        target: Vec2,
+        facing: f32,
+        velocity: Vec2,
+        delta: f32,
+        level_settings: &LevelSettings,
+    ) -> Vec2 {
+        let smoothing = level_settings.camera_smoothing.unwrap_or(self.smoothing);
+        let deadzone = Vec2::new(
+            level_settings.camera_deadzone_x.unwrap_or(self.deadzone.x),
+            level_settings.camera_deadzone_y.unwrap_or(self.deadzone.y),
+        );
+        let look_ahead = level_settings.camera_look_ahead.unwrap_or(self.look_ahead);
+        let fall_bias = level_settings.camera_fall_bias.unwrap_or(self.fall_bias);
+
+        // frame rate independent exponential smoothing.
+        let t = 1. - (-smoothing * delta).exp();
+
+        let fall_target = if velocity.y < -self.fall_speed {
+            fall_bias
+        } else {
+            0.
+        };
+        self.look_ahead_offset += (look_ahead * facing - self.look_ahead_offset) * t;
+        self.fall_offset += (fall_target - self.fall_offset) * t;
+
+        let focus = target + Vec2::new(self.look_ahead_offset, -self.fall_offset);
+        let offset = focus - camera;
+        // only what sticks out of the deadzone moves the camera.
+        let excess = offset.signum() * (offset.abs() - deadzone).max(Vec2::ZERO);
+
+        camera + excess * t
+    }
+
+    /// Forgets the eased offsets, for when the camera is snapped onto its target.
+    pub fn reset(&mut self) {
+        self.look_ahead_offset = 0.;
+        self.fall_offset = 0.;
+    }
+}
+
+/// Screen shake driven by trauma, which [`CameraTrauma`] events add to and which wears off over
+/// time. The shake grows with the square of the trauma, so small knocks stay subtle.
+#[derive(Component, Debug, Clone)]
+pub struct CameraShake {
+    /// From `0.` to `1.`.
+    pub trauma: f32,
+    /// Trauma lost per second.
+    pub decay: f32,
+    /// Offset in pixels at full trauma.
+    pub max_offset: Vec2,
+    /// Roll in radians at full trauma.
+    pub max_roll: f32,
+    /// How quickly the shake wobbles.
+    pub frequency: f32,
+}
+
+impl Default for CameraShake {
+    fn default() -> Self {
+        Self {
+            trauma: 0.,
+            decay: 1.5,
+            max_offset: Vec2::new(8., 6.),
+            max_roll: 0.05,
+            frequency: 25.,
+        }
+    }
+}
+
+impl CameraShake {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0., 1.);
+    }
+}
+
+/// Adds its amount of trauma to the [`CameraShake`].
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CameraTrauma(pub f32);
+
+/// Eases the camera's projection scale towards `scale`, or towards the zoom of the [`CameraZone`]
+/// the player is in.
+#[derive(Component, Debug, Clone)]
+pub struct CameraZoom {
+    pub scale: f32,
+    /// How quickly the zoom catches up, higher is snappier.
+    pub speed: f32,
+}
+
+impl Default for CameraZoom {
+    fn default() -> Self {
+        Self {
+            scale: 1.,
+            speed: 4.,
+        }
+    }
+}
+
+fn spawn_camera(mut commands: Commands, canvas: Res<PixelCanvas>) {
+    commands.spawn((
+        Camera2d,
+        Camera {
+            // one world unit per canvas pixel, upscaled to the window afterwards.
+            target: RenderTarget::Image(canvas.image.clone()),
+            order: -1,
+            ..default()
+        },
+        Msaa::Off,
+        CameraController::default(),
+        CameraShake::default(),
+        CameraZoom::default(),
+    ));
+}
+
+fn add_camera_trauma(
+    mut trauma: EventReader<CameraTrauma>,
+    death_sequence: Res<DeathSequence>,
+    mut was_alive: Local<bool>,
+    mut shake: Single<&mut CameraShake>,
+) {
+    for CameraTrauma(amount) in trauma.read() {
+        shake.add_trauma(*amount);
+    }
+
+    // once per death, however often the player is killed while already dying.
+    let alive = death_sequence.is_alive();
+    if *was_alive && !alive {
+        shake.add_trauma(DEATH_TRAUMA);
+    }
+    *was_alive = alive;
+}
+
+pub(super) fn shake_camera(
+    mut camera: Query<(&mut CameraShake, &mut GlobalTransform)>,
+    time: Res<Time>,
+) {
+    let Ok((mut shake, mut transform)) = camera.get_single_mut() else {
+        return;
+    };
+
+    shake.trauma = (shake.trauma - shake.decay * time.delta_secs()).max(0.);
+    if shake.trauma <= 0. {
+        return;
+    }
+
+    // a couple of out of phase sines per channel, smooth but never quite repeating.
+    let t = time.elapsed_secs() * shake.frequency;
+    let wobble = |seed: f32| ((t + seed).sin() + (t * 2.31 + seed * 1.7).sin() * 0.5) / 1.5;
+
+    let strength = shake.trauma * shake.trauma;
+    let offset = Vec2::new(wobble(0.), wobble(11.)) * shake.max_offset * strength;
+    let roll = wobble(23.) * shake.max_roll * strength;
+
+    *transform = transform.mul_transform(Transform {
+        translation: offset.extend(0.),
+        rotation: Quat::from_rotation_z(roll),
+        ..default()
+    });
+}
+
+fn zoom_camera(
+    mut camera: Query<(&CameraZoom, &mut OrthographicProjection)>,
+    zones: Query<&CameraZone>,
+    active_zone: Res<ActiveCameraZone>,
+    time: Res<Time>,
+) {
+    let Ok((zoom, mut projection)) = camera.get_single_mut() else {
+        return;
+    };
+
+    let target = active_zone
+        .0
+        .and_then(|zone| zones.get(zone).ok())
+        .and_then(|zone| zone.zoom)
+        .unwrap_or(zoom.scale);
+
+    let t = 1. - (-zoom.speed * time.delta_secs()).exp();
+    let scale = projection.scale + (target - projection.scale) * t;
+    // don't keep touching the projection once it's close enough.
+    if (scale - projection.scale).abs() > f32::EPSILON {
+        projection.scale = scale;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn clamp_camera_to_level(
+    mut camera: Query<(&mut Transform, &OrthographicProjection), With<CameraController>>,
+    levels: Query<(Entity, &LevelIid, &GlobalTransform)>,
+    level_selection: Res<LevelSelection>,
+    level_settings: Res<LevelSettings>,
+    zones: Query<(&CameraZone, &GlobalTransform)>,
+    active_zone: Res<ActiveCameraZone>,
+    handle: Single<&LdtkProjectHandle>,
+    projects: Res<Assets<LdtkProject>>,
+) {
+    let Ok((mut transform, projection)) = camera.get_single_mut() else {
+        return;
+    };
+    let zone = active_zone.0.and_then(|zone| zones.get(zone).ok());
+    let Some(project) = projects.get(&handle.handle) else {
+        return;
+    };
+    let Some((level, _, level_transform)) =
+        selected_level_entity(project, &level_selection, &levels)
+    else {
+        return;
+    };
+
+    let level_rect = level_bounds(level, level_transform);
+    let grid_size = project.json_data().default_grid_size as f32;
+    // LDtk counts cells from the top left, bevy from the bottom left.
+    let cell_corner = |cell: IVec2| {
+        Vec2::new(
+            level_rect.min.x + cell.x as f32 * grid_size,
+            level_rect.max.y - cell.y as f32 * grid_size,
+        )
+    };
+
+    let bounds = match zone {
+        Some((zone, zone_transform)) if zone.mode == CameraZoneMode::Frame => {
+            zone.rect(zone_transform)
+        }
+        _ => {
+            let mut bounds = level_rect;
+            if let Some(min) = *level_settings.camera_bounds_min {
+                let top_left = cell_corner(min);
+                bounds.min.x = top_left.x;
+                bounds.max.y = top_left.y;
+            }
+            if let Some(max) = *level_settings.camera_bounds_max {
+                let bottom_right = cell_corner(max + 1);
+                bounds.max.x = bottom_right.x;
+                bounds.min.y = bottom_right.y;
+            }
+            bounds
+        }
+    };
+
+    // the projection's area is in camera space, which the camera's own scale then stretches.
+    let half_view = projection.area.half_size() * transform.scale.truncate();
+    let position = clamp_to_bounds(transform.translation.truncate(), bounds, half_view);
+    transform.translation.x = position.x;
+    transform.translation.y = position.y;
+}
+
+/// Keeps a view of `half_view` centred at `position` inside `bounds`, centring it on any axis the
+/// bounds are too small to fill.
+fn clamp_to_bounds(position: Vec2, bounds: Rect, half_view: Vec2) -> Vec2 {
+    let clamp_axis = |position: f32, min: f32, max: f32, half_view: f32| {
+        if max - min <= half_view * 2. {
+            (min + max) / 2.
+        } else {
+            position.clamp(min + half_view, max - half_view)
+        }
+    };
+
+    Vec2::new(
+        clamp_axis(position.x, bounds.min.x, bounds.max.x, half_view.x),
+        clamp_axis(position.y, bounds.min.y, bounds.max.y, half_view.y),
+    )
 }
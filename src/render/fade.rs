@@ -0,0 +1,55 @@
+use bevy::{
+    app::{Plugin, Startup, Update},
+    color::{Alpha, Color},
+    prelude::{
+        BackgroundColor, Commands, Component, DetectChanges, GlobalZIndex, Node, PositionType, Res,
+        Resource, Single, Val, With,
+    },
+    ui::FocusPolicy,
+    utils::default,
+};
+
+#[derive(Default)]
+pub(super) struct FadePlugin;
+
+impl Plugin for FadePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<ScreenFade>()
+            .add_systems(Startup, spawn_fade_overlay)
+            .add_systems(Update, update_fade_overlay);
+    }
+}
+
+/// How much of the screen is covered in black, from `0.` (fully visible) to `1.`.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct ScreenFade(pub f32);
+
+#[derive(Component)]
+struct FadeOverlay;
+
+fn spawn_fade_overlay(mut commands: Commands) {
+    commands.spawn((
+        FadeOverlay,
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            ..default()
+        },
+        BackgroundColor(Color::BLACK.with_alpha(0.)),
+        FocusPolicy::Pass,
+        // above any other UI.
+        GlobalZIndex(i32::MAX),
+    ));
+}
+
+fn update_fade_overlay(
+    fade: Res<ScreenFade>,
+    mut overlay: Single<&mut BackgroundColor, With<FadeOverlay>>,
+) {
+    if !fade.is_changed() {
+        return;
+    }
+
+    overlay.0 = Color::BLACK.with_alpha(fade.0.clamp(0., 1.));
+}
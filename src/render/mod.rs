@@ -1,11 +1,15 @@
 use bevy::app::plugin_group;
 
 pub mod animation;
-mod camera;
+pub mod camera;
+pub mod fade;
+pub mod pixel;
 
 plugin_group! {
     pub struct RenderPlugins {
         camera:::CameraPlugin,
-        animation:::AnimationPlugin
+        animation:::AnimationPlugin,
+        fade:::FadePlugin,
+        pixel:::PixelPerfectPlugin
     }
 }
@@ -0,0 +1,154 @@
+use bevy::{
+    app::{Plugin, PostUpdate, Startup, Update},
+    asset::{Assets, Handle, RenderAssetUsages},
+    color::Color,
+    math::{UVec2, Vec3},
+    prelude::{
+        Camera, Camera2d, Changed, ClearColorConfig, Commands, Component, FromWorld,
+        GlobalTransform, Image, IntoSystemConfigs, IsDefaultUiCamera, Msaa, Or, Query, Res,
+        Resource, Single, Transform, TransformSystem, With, Without, World,
+    },
+    render::{
+        render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+        view::RenderLayers,
+    },
+    sprite::Sprite,
+    utils::default,
+    window::{PrimaryWindow, Window},
+};
+
+/// Render layer of the upscaled canvas, kept away from everything in the world.
+const CANVAS_LAYER: usize = 1;
+
+#[derive(Default)]
+pub(super) struct PixelPerfectPlugin;
+
+impl Plugin for PixelPerfectPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<VirtualResolution>()
+            .add_systems(Startup, spawn_canvas)
+            .add_systems(Update, fit_canvas)
+            .add_systems(
+                PostUpdate,
+                snap_to_pixels
+                    .after(TransformSystem::TransformPropagate)
+                    .after(super::camera::shake_camera),
+            );
+    }
+
+    fn finish(&self, app: &mut bevy::prelude::App) {
+        // needs the image assets, which only exist once every plugin is built.
+        app.init_resource::<PixelCanvas>();
+    }
+}
+
+/// The size in pixels the world is rendered at before being upscaled to the window. Only read at
+/// startup.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct VirtualResolution(pub UVec2);
+
+impl Default for VirtualResolution {
+    fn default() -> Self {
+        // 1080p divided by 5.
+        Self(UVec2::new(384, 216))
+    }
+}
+
+/// The low resolution image the world camera renders to.
+#[derive(Resource, Debug)]
+pub struct PixelCanvas {
+    pub image: Handle<Image>,
+}
+
+impl FromWorld for PixelCanvas {
+    fn from_world(world: &mut World) -> Self {
+        let resolution = world.resource::<VirtualResolution>().0;
+        let mut image = Image::new_fill(
+            Extent3d {
+                width: resolution.x,
+                height: resolution.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0, 0, 0, 0],
+            TextureFormat::Bgra8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
+            | TextureUsages::COPY_DST
+            | TextureUsages::RENDER_ATTACHMENT;
+
+        Self {
+            image: world.resource_mut::<Assets<Image>>().add(image),
+        }
+    }
+}
+
+#[derive(Component)]
+struct CanvasSprite;
+
+/// Draws the canvas to the window, the area it leaves uncovered is letterboxed in black.
+#[derive(Component)]
+struct CanvasCamera;
+
+fn spawn_canvas(mut commands: Commands, canvas: Res<PixelCanvas>) {
+    commands.spawn((
+        CanvasSprite,
+        Sprite::from_image(canvas.image.clone()),
+        RenderLayers::layer(CANVAS_LAYER),
+    ));
+    commands.spawn((
+        CanvasCamera,
+        Camera2d,
+        Camera {
+            // after the world camera has filled the canvas.
+            order: 1,
+            clear_color: ClearColorConfig::Custom(Color::BLACK),
+            ..default()
+        },
+        Msaa::Off,
+        RenderLayers::layer(CANVAS_LAYER),
+        // the ui is drawn at the window's resolution rather than the canvas'.
+        IsDefaultUiCamera,
+    ));
+}
+
+fn fit_canvas(
+    window: Query<&Window, (With<PrimaryWindow>, Changed<Window>)>,
+    resolution: Res<VirtualResolution>,
+    mut canvas: Single<&mut Transform, With<CanvasSprite>>,
+) {
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+
+    let physical = window.physical_size();
+    let factor = (physical / resolution.0).min_element().max(1);
+    let leftover = physical.saturating_sub(resolution.0 * factor);
+
+    // the canvas camera works in logical pixels, the canvas has to line up with physical ones.
+    let scale = window.scale_factor();
+    canvas.scale = Vec3::splat(factor as f32 / scale);
+    // an odd letterbox would put the canvas' edges halfway into a pixel.
+    canvas.translation.x = (leftover.x % 2) as f32 * 0.5 / scale;
+    canvas.translation.y = (leftover.y % 2) as f32 * 0.5 / scale;
+}
+
+// only the rendered position is snapped, the transforms themselves keep moving smoothly.
+#[allow(clippy::type_complexity)]
+fn snap_to_pixels(
+    mut transforms: Query<
+        &mut GlobalTransform,
+        (
+            Or<(With<Sprite>, With<Camera2d>)>,
+            Without<CanvasSprite>,
+            Without<CanvasCamera>,
+        ),
+    >,
+) {
+    for mut transform in &mut transforms {
+        let mut affine = transform.affine();
+        affine.translation = affine.translation.round();
+        *transform = GlobalTransform::from(affine);
+    }
+}
@@ -1,10 +1,15 @@
+use std::{marker::PhantomData, time::Duration};
+
 use bevy::{
+    asset::{io::Reader, Asset, AssetLoader, LoadContext},
     math::Rect,
     prelude::{Commands, Component},
+    time::{Timer, TimerMode},
 };
 use bevy_ecs_ldtk::app::{LdtkEntityAppExt, LdtkIntCellAppExt};
+use serde::de::DeserializeOwned;
 
-use crate::sealed_trait;
+use crate::{sealed_trait, world::validation::LdtkRegistry};
 
 pub trait SystemDefault {
     fn sdefault() -> Self;
@@ -24,6 +29,11 @@ where
     commands.spawn(T::sdefault());
 }
 
+/// A one-shot timer for a phase lasting `secs`, finished right away when it's negative.
+pub fn phase_timer(secs: f32) -> Timer {
+    Timer::new(Duration::from_secs_f32(secs.max(0.)), TimerMode::Once)
+}
+
 sealed_trait! {
     pub trait RectExt {
         fn intersects(&self, other: Rect) -> bool;
@@ -55,6 +65,9 @@ impl LdtkAppTraitExt for bevy::app::App {
     where
         B: bevy::prelude::Bundle + bevy_ecs_ldtk::prelude::LdtkEntity + crate::world::Entity,
     {
+        self.world_mut()
+            .get_resource_or_insert_with(LdtkRegistry::default)
+            .record_entity::<B>();
         <Self as LdtkEntityAppExt>::register_ldtk_entity::<B>(self, B::IDENTIFIER)
     }
 
@@ -62,6 +75,55 @@ impl LdtkAppTraitExt for bevy::app::App {
     where
         B: bevy::prelude::Bundle + bevy_ecs_ldtk::prelude::LdtkIntCell + crate::world::IntCell,
     {
-        <Self as LdtkIntCellAppExt>::register_ldtk_int_cell::<B>(self, B::INTCELL_ID)
+        self.world_mut()
+            .get_resource_or_insert_with(LdtkRegistry::default)
+            .record_int_cell::<B>();
+        match B::LAYER {
+            Some(layer) => <Self as LdtkIntCellAppExt>::register_ldtk_int_cell_for_layer::<B>(
+                self,
+                layer,
+                B::INTCELL_ID,
+            ),
+            None => <Self as LdtkIntCellAppExt>::register_ldtk_int_cell::<B>(self, B::INTCELL_ID),
+        }
+    }
+}
+
+/// Loads any deserializable asset from a JSON file.
+pub struct JsonAssetLoader<A> {
+    extensions: &'static [&'static str],
+    _marker: PhantomData<fn() -> A>,
+}
+
+impl<A> JsonAssetLoader<A> {
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        Self {
+            extensions,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<A> AssetLoader for JsonAssetLoader<A>
+where
+    A: Asset + DeserializeOwned,
+{
+    type Asset = A;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<A, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
     }
 }
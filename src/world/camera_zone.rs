@@ -0,0 +1,87 @@
+use bevy::{
+    math::{Rect, Vec2},
+    prelude::{
+        Bundle, Component, DetectChangesMut, Entity, GlobalTransform, Query, ResMut, Resource, With,
+    },
+};
+use bevy_ecs_ldtk::{prelude::LdtkFields, EntityInstance, LdtkEntity};
+
+use super::level_settings::FromFieldValue;
+use crate::{impl_entity, level_enum, player::Player};
+
+level_enum! {
+    /// What a [`CameraZone`] does to the camera while the player is inside.
+    #[derive(Default)]
+    pub enum CameraZoneMode {
+        /// Keep following the player, but never show anything outside the zone.
+        #[default]
+        Frame,
+        /// Hold the camera still on the zone's centre.
+        Lock,
+    }
+}
+
+/// An area of a level taking over the camera while the player is inside.
+#[derive(Component, Debug, Default)]
+pub struct CameraZone {
+    pub mode: CameraZoneMode,
+    pub size: Vec2,
+    /// Projection scale to ease towards, the camera's own when absent.
+    pub zoom: Option<f32>,
+}
+
+impl CameraZone {
+    fn from_entity_instance(entity_instance: &EntityInstance) -> Self {
+        let mode = entity_instance
+            .get_field_instance("Mode")
+            .ok()
+            .and_then(|field| CameraZoneMode::from_field_value(field.value.clone()).ok())
+            .unwrap_or_default();
+
+        Self {
+            mode,
+            size: Vec2::new(entity_instance.width as f32, entity_instance.height as f32),
+            zoom: entity_instance.get_float_field("Zoom").ok().copied(),
+        }
+    }
+
+    pub fn rect(&self, transform: &GlobalTransform) -> Rect {
+        Rect::from_center_size(transform.translation().truncate(), self.size)
+    }
+}
+
+#[derive(Default, Bundle, LdtkEntity)]
+pub(super) struct CameraZoneBundle {
+    #[with(CameraZone::from_entity_instance)]
+    zone: CameraZone,
+}
+
+impl_entity!(CameraZoneBundle | "CameraZone");
+
+/// The [`CameraZone`] the player is in, if any.
+#[derive(Resource, Debug, Default, PartialEq, Eq)]
+pub struct ActiveCameraZone(pub Option<Entity>);
+
+pub(super) fn enter_camera_zone(
+    player: Query<&GlobalTransform, With<Player>>,
+    zones: Query<(Entity, &CameraZone, &GlobalTransform)>,
+    mut active: ResMut<ActiveCameraZone>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    let player = player.translation().truncate();
+    let contains = |(_, zone, transform): &(Entity, &CameraZone, &GlobalTransform)| {
+        zone.rect(transform).contains(player)
+    };
+
+    // where zones overlap, the one entered first keeps the camera until the player leaves it.
+    if let Some(current) = active.0.and_then(|entity| zones.get(entity).ok()) {
+        if contains(&current) {
+            return;
+        }
+    }
+
+    let next = zones.iter().find(contains).map(|(entity, ..)| entity);
+    active.set_if_neq(ActiveCameraZone(next));
+}
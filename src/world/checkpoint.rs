@@ -0,0 +1,98 @@
+use avian2d::prelude::{Collider, CollidingEntities, RigidBody, Sensor};
+use bevy::{
+    asset::Assets,
+    prelude::{
+        Bundle, Component, Entity, Event, EventWriter, GlobalTransform, Parent, Query, Res, ResMut,
+        Single, With,
+    },
+    sprite::Sprite,
+};
+use bevy_ecs_ldtk::{
+    assets::LdtkProject, EntityInstance, LdtkEntity, LdtkProjectHandle, LevelIid, LevelSelection,
+};
+
+use super::{
+    death::RespawnPoint,
+    level::{on_level_layer, selected_level_entity},
+    spawn::player_position_at,
+    Entity as _,
+};
+use crate::{impl_entity, player::Player};
+
+#[derive(Component, Default)]
+pub struct Checkpoint;
+
+#[derive(Default, Bundle, LdtkEntity)]
+pub(super) struct CheckpointBundle {
+    _marker: Checkpoint,
+    #[sprite_sheet]
+    sprite: Sprite,
+    #[with(checkpoint_collider)]
+    collider: Collider,
+    #[with(checkpoint_rigid_body)]
+    rigid_body: RigidBody,
+    sensor: Sensor,
+    colliding: CollidingEntities,
+}
+
+impl_entity!(CheckpointBundle | "Checkpoint"; 16.);
+
+fn checkpoint_collider(_: &EntityInstance) -> Collider {
+    let (width, height) = CheckpointBundle::DIMENSIONS.unwrap_or_default();
+    Collider::rectangle(width, height)
+}
+
+fn checkpoint_rigid_body(_: &EntityInstance) -> RigidBody {
+    RigidBody::Static
+}
+
+/// Sent when the player reaches a checkpoint that wasn't already the [`RespawnPoint`].
+#[derive(Event, Debug)]
+pub struct CheckpointActivated {
+    pub checkpoint: Entity,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) fn activate_checkpoint(
+    player: Query<Entity, With<Player>>,
+    checkpoints: Query<(Entity, &CollidingEntities, &GlobalTransform, &Parent), With<Checkpoint>>,
+    layers: Query<&Parent>,
+    levels: Query<(Entity, &LevelIid, &GlobalTransform)>,
+    level_selection: Res<LevelSelection>,
+    handle: Single<&LdtkProjectHandle>,
+    projects: Res<Assets<LdtkProject>>,
+    mut respawn_point: ResMut<RespawnPoint>,
+    mut activated: EventWriter<CheckpointActivated>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    let Some(project) = projects.get(&handle.handle) else {
+        return;
+    };
+    let Some((_, level_entity, _)) = selected_level_entity(project, &level_selection, &levels)
+    else {
+        return;
+    };
+
+    // neighbouring levels are loaded too, only the selected level's checkpoints count.
+    let Some((checkpoint, _, transform, _)) =
+        checkpoints.iter().find(|(_, colliding, _, layer)| {
+            colliding.contains(&player) && on_level_layer(layer, level_entity, &layers)
+        })
+    else {
+        return;
+    };
+
+    if respawn_point.checkpoint == Some(checkpoint) {
+        return;
+    }
+
+    let grid_size = project.json_data().default_grid_size as f32;
+    respawn_point.position = Some(player_position_at(
+        transform.translation().truncate(),
+        grid_size,
+    ));
+    respawn_point.checkpoint = Some(checkpoint);
+    activated.send(CheckpointActivated { checkpoint });
+}
@@ -0,0 +1,141 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use avian2d::prelude::{Collider, Friction, Restitution, RigidBody};
+use bevy::{
+    hierarchy::{BuildChildren, ChildBuild, DespawnRecursiveExt},
+    math::{IRect, IVec2},
+    prelude::{Added, Children, Commands, Component, Entity, Parent, Query, Transform, With},
+};
+use bevy_ecs_ldtk::{prelude::LayerMetadata, GridCoords, IntGridCell};
+use bevy_tnua::TnuaGhostPlatform;
+
+use super::platform::OneWayPlatform;
+
+/// An IntGrid cell that is solid ground. Rather than one body per cell, contiguous cells sharing a
+/// value are merged into as few rectangle colliders as possible once their layer spawns.
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub struct SolidCell {
+    pub friction: Friction,
+    pub restitution: Restitution,
+    /// Merge into a [`OneWayPlatform`] instead.
+    pub one_way: bool,
+}
+
+/// A collider covering several [`SolidCell`]s, spawned as a child of their layer.
+#[derive(Component, Debug)]
+pub struct MergedCollider {
+    pub value: i32,
+    /// In grid coordinates, both corners inclusive.
+    pub cells: IRect,
+}
+
+pub(super) fn merge_solid_cells(
+    mut commands: Commands,
+    new_cells: Query<&Parent, Added<SolidCell>>,
+    cells: Query<(&GridCoords, &IntGridCell, &SolidCell, &Parent)>,
+    layers: Query<(&LayerMetadata, Option<&Children>)>,
+    merged: Query<(), With<MergedCollider>>,
+) {
+    // a (re)spawned level spawns all of its cells in the same frame, so each layer is rebuilt whole.
+    let dirty_layers: HashSet<Entity> = new_cells.iter().map(|layer| layer.get()).collect();
+
+    for layer in dirty_layers {
+        let Ok((metadata, children)) = layers.get(layer) else {
+            continue;
+        };
+
+        for &child in children.into_iter().flatten() {
+            if merged.contains(child) {
+                commands.entity(child).despawn_recursive();
+            }
+        }
+
+        // cells sharing a value are bound to the same bundle, and so share their material too.
+        let mut by_value: HashMap<i32, (SolidCell, Vec<IVec2>)> = HashMap::new();
+        for (coords, cell, solid, _) in cells.iter().filter(|(.., parent)| parent.get() == layer) {
+            by_value
+                .entry(cell.value)
+                .or_insert_with(|| (*solid, Vec::new()))
+                .1
+                .push(IVec2::new(coords.x, coords.y));
+        }
+
+        let grid_size = metadata.grid_size as f32;
+        commands.entity(layer).with_children(|layer| {
+            for (value, (solid, cells)) in by_value {
+                for rect in merge_cells(cells) {
+                    let size = (rect.size() + IVec2::ONE).as_vec2() * grid_size;
+                    let center = rect.min.as_vec2() * grid_size + size / 2.;
+
+                    let mut collider = layer.spawn((
+                        MergedCollider { value, cells: rect },
+                        Collider::rectangle(size.x, size.y),
+                        RigidBody::Static,
+                        solid.friction,
+                        solid.restitution,
+                        Transform::from_translation(center.extend(0.)),
+                    ));
+                    if solid.one_way {
+                        // tnua only floats on ghost platforms when the player isn't dropping through.
+                        collider.insert((OneWayPlatform::default(), TnuaGhostPlatform));
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Covers the cells with rectangles, first joining each row into runs and then stacking runs of
+/// equal extent on consecutive rows. Corners are inclusive.
+fn merge_cells(cells: impl IntoIterator<Item = IVec2>) -> Vec<IRect> {
+    let mut rows: BTreeMap<i32, Vec<i32>> = BTreeMap::new();
+    for cell in cells {
+        rows.entry(cell.y).or_default().push(cell.x);
+    }
+
+    let mut done = Vec::new();
+    // rects still growing upwards, keyed by their horizontal extent.
+    let mut open: HashMap<(i32, i32), IRect> = HashMap::new();
+    let mut previous_row = None;
+
+    for (y, mut xs) in rows {
+        xs.sort_unstable();
+        xs.dedup();
+
+        let mut runs = Vec::new();
+        let mut run = (xs[0], xs[0]);
+        for &x in &xs[1..] {
+            if x == run.1 + 1 {
+                run.1 = x;
+            } else {
+                runs.push(run);
+                run = (x, x);
+            }
+        }
+        runs.push(run);
+
+        // a gap between rows ends every rect.
+        if previous_row != Some(y - 1) {
+            done.extend(open.drain().map(|(_, rect)| rect));
+        }
+
+        let mut next = HashMap::new();
+        for run in runs {
+            let rect = match open.remove(&run) {
+                Some(mut rect) => {
+                    rect.max.y = y;
+                    rect
+                }
+                None => IRect::new(run.0, y, run.1, y),
+            };
+            next.insert(run, rect);
+        }
+
+        done.extend(open.into_values());
+        open = next;
+        previous_row = Some(y);
+    }
+
+    done.extend(open.into_values());
+    done
+}
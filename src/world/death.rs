@@ -0,0 +1,174 @@
+use avian2d::prelude::{LinearVelocity, Physics, PhysicsTime};
+use bevy::{
+    log::warn,
+    math::Vec2,
+    prelude::{
+        Entity, Event, EventReader, EventWriter, Query, Res, ResMut, Resource, Transform,
+        Visibility, With,
+    },
+    time::{Time, Timer},
+};
+
+use super::spawn::PendingSpawn;
+use crate::{
+    player::{Player, SyncCameraWithPlayer},
+    render::fade::ScreenFade,
+    utils::phase_timer,
+};
+
+/// Kills the player, sending them back to the [`RespawnPoint`] through the [`DeathSequence`].
+#[derive(Event, Debug, Default)]
+pub struct PlayerDied;
+
+/// Sent once the player has been put back at the [`RespawnPoint`].
+#[derive(Event, Debug)]
+pub struct PlayerRespawned {
+    pub position: Vec2,
+}
+
+/// Where the player comes back after dying. Set to the level's spawn on entry and moved by every
+/// activated [`Checkpoint`](super::checkpoint::Checkpoint).
+#[derive(Resource, Debug, Default)]
+pub struct RespawnPoint {
+    pub position: Option<Vec2>,
+    /// The checkpoint the position comes from, `None` for the level's spawn.
+    pub checkpoint: Option<Entity>,
+}
+
+/// How long each step of dying takes, in seconds. A step of `0.` is skipped.
+#[derive(Resource, Debug, Clone)]
+pub struct DeathSequenceConfig {
+    /// Physics stop, so the moment of death registers.
+    pub freeze: f32,
+    /// The player blinks in place.
+    pub animation: f32,
+    pub fade_out: f32,
+    pub fade_in: f32,
+    /// Seconds between blinks during the animation.
+    pub blink_interval: f32,
+}
+
+impl Default for DeathSequenceConfig {
+    fn default() -> Self {
+        Self {
+            freeze: 0.15,
+            animation: 0.5,
+            fade_out: 0.3,
+            fade_in: 0.3,
+            blink_interval: 0.08,
+        }
+    }
+}
+
+/// Where the player is between dying and respawning. Physics stays paused until the respawn.
+#[derive(Resource, Debug, Default)]
+pub enum DeathSequence {
+    #[default]
+    Alive,
+    Freezing(Timer),
+    Animating(Timer),
+    FadingOut(Timer),
+    FadingIn(Timer),
+}
+
+impl DeathSequence {
+    pub fn is_alive(&self) -> bool {
+        matches!(self, Self::Alive)
+    }
+}
+
+/// Run condition keeping hazards from killing the player again while they're already dying.
+pub(super) fn player_alive(sequence: Res<DeathSequence>) -> bool {
+    sequence.is_alive()
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) fn run_death_sequence(
+    mut died: EventReader<PlayerDied>,
+    mut sequence: ResMut<DeathSequence>,
+    config: Res<DeathSequenceConfig>,
+    time: Res<Time>,
+    mut physics_time: ResMut<Time<Physics>>,
+    mut fade: ResMut<ScreenFade>,
+    respawn_point: Res<RespawnPoint>,
+    mut pending_spawn: ResMut<PendingSpawn>,
+    mut player: Query<(&mut Transform, &mut LinearVelocity, &mut Visibility), With<Player>>,
+    mut respawned: EventWriter<PlayerRespawned>,
+    mut sync_camera: EventWriter<SyncCameraWithPlayer>,
+) {
+    // dying again mid-sequence, or twice in a frame, still means a single respawn.
+    let just_died = died.read().count() > 0;
+
+    let Ok((mut transform, mut velocity, mut visibility)) = player.get_single_mut() else {
+        return;
+    };
+
+    if sequence.is_alive() {
+        if just_died {
+            physics_time.pause();
+            *sequence = DeathSequence::Freezing(phase_timer(config.freeze));
+        }
+        return;
+    }
+
+    let (DeathSequence::Freezing(timer)
+    | DeathSequence::Animating(timer)
+    | DeathSequence::FadingOut(timer)
+    | DeathSequence::FadingIn(timer)) = sequence.as_mut()
+    else {
+        return;
+    };
+
+    timer.tick(time.delta());
+    let progress = timer.fraction();
+    let finished = timer.finished();
+    let elapsed = timer.elapsed_secs();
+
+    match sequence.as_ref() {
+        DeathSequence::Animating(_) if config.blink_interval > 0. => {
+            let blink = (elapsed / config.blink_interval) as u32 % 2 == 1;
+            *visibility = if blink {
+                Visibility::Hidden
+            } else {
+                Visibility::Inherited
+            };
+        }
+        DeathSequence::FadingOut(_) => fade.0 = progress,
+        DeathSequence::FadingIn(_) => fade.0 = 1. - progress,
+        _ => (),
+    }
+
+    if !finished {
+        return;
+    }
+
+    let next = match sequence.as_ref() {
+        DeathSequence::Freezing(_) => DeathSequence::Animating(phase_timer(config.animation)),
+        DeathSequence::Animating(_) => {
+            *visibility = Visibility::Inherited;
+            DeathSequence::FadingOut(phase_timer(config.fade_out))
+        }
+        DeathSequence::FadingOut(_) => {
+            if let Some(position) = respawn_point.position {
+                transform.translation.x = position.x;
+                transform.translation.y = position.y;
+                *velocity = LinearVelocity::ZERO;
+
+                respawned.send(PlayerRespawned { position });
+                sync_camera.send_default();
+            } else {
+                // left where they died, they'd only die again. the level's own spawn will do.
+                warn!("No respawn point, sending the player back to the level's spawn");
+                *velocity = LinearVelocity::ZERO;
+                *pending_spawn = PendingSpawn::Default;
+            }
+            physics_time.unpause();
+            DeathSequence::FadingIn(phase_timer(config.fade_in))
+        }
+        DeathSequence::FadingIn(_) | DeathSequence::Alive => {
+            fade.0 = 0.;
+            DeathSequence::Alive
+        }
+    };
+    *sequence = next;
+}
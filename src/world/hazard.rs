@@ -0,0 +1,101 @@
+use avian2d::prelude::{Collider, CollisionStarted, RigidBody, Sensor};
+use bevy::{
+    asset::Assets,
+    prelude::{
+        Bundle, Component, Entity, EventReader, EventWriter, GlobalTransform, Query, Res, Single,
+        With,
+    },
+};
+use bevy_ecs_ldtk::{
+    app::LdtkIntCell, assets::LdtkProject, LdtkProjectHandle, LevelIid, LevelSelection,
+};
+
+use super::{death::PlayerDied, level::selected_level_entity};
+use crate::{
+    impl_intcell,
+    player::{Player, PLAYER_DIM},
+};
+
+/// Kills the player on touch.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hazard {
+    Spikes,
+    Lava,
+    Pit,
+}
+
+/// `$scale` sizes the sensor relative to the layer's grid.
+macro_rules! hazard_bundle {
+    ($name:ident: $hazard:expr, $scale:expr) => {
+        #[derive(Bundle)]
+        pub struct $name {
+            hazard: Hazard,
+            collider: Collider,
+            rigidbody: RigidBody,
+            sensor: Sensor,
+        }
+
+        impl LdtkIntCell for $name {
+            fn bundle_int_cell(
+                _int_grid_cell: bevy_ecs_ldtk::IntGridCell,
+                layer_instance: &bevy_ecs_ldtk::prelude::LayerInstance,
+            ) -> Self {
+                let size = layer_instance.grid_size as f32 * $scale;
+                Self {
+                    hazard: $hazard,
+                    collider: Collider::rectangle(size, size),
+                    rigidbody: RigidBody::Static,
+                    sensor: Sensor,
+                }
+            }
+        }
+    };
+}
+
+// a bit smaller than a cell, grazing the tips shouldn't kill.
+hazard_bundle!(SpikesBundle: Hazard::Spikes, 0.65);
+hazard_bundle!(LavaBundle: Hazard::Lava, 1.);
+hazard_bundle!(PitBundle: Hazard::Pit, 1.);
+
+impl_intcell!(SpikesBundle | "Terrain": 2; 19.);
+impl_intcell!(LavaBundle | "Terrain": 3; 19.);
+impl_intcell!(PitBundle | "Terrain": 4; 19.);
+
+pub(super) fn touch_hazard(
+    mut collisions: EventReader<CollisionStarted>,
+    hazards: Query<(), With<Hazard>>,
+    player: Query<(), With<Player>>,
+    mut died: EventWriter<PlayerDied>,
+) {
+    for CollisionStarted(a, b) in collisions.read() {
+        let touched = (player.contains(*a) && hazards.contains(*b))
+            || (player.contains(*b) && hazards.contains(*a));
+        if touched {
+            died.send_default();
+        }
+    }
+}
+
+pub(super) fn fall_out_of_level(
+    player: Query<&GlobalTransform, With<Player>>,
+    levels: Query<(Entity, &LevelIid, &GlobalTransform)>,
+    level_selection: Res<LevelSelection>,
+    handle: Single<&LdtkProjectHandle>,
+    projects: Res<Assets<LdtkProject>>,
+    mut died: EventWriter<PlayerDied>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    let Some((_, _, level_transform)) = projects
+        .get(&handle.handle)
+        .and_then(|project| selected_level_entity(project, &level_selection, &levels))
+    else {
+        return;
+    };
+
+    // levels are anchored at their bottom left corner.
+    if player.translation().y + PLAYER_DIM.1 / 2. < level_transform.translation().y {
+        died.send_default();
+    }
+}
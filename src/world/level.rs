@@ -1,87 +1,371 @@
+use avian2d::prelude::{Collider, CollisionStarted, RigidBody, Sensor};
 use bevy::{
+    asset::Assets,
     log::warn,
+    math::{Rect, Vec2},
     prelude::{
-        Bundle, Component, DetectChanges, Event, EventReader, EventWriter, Local, Query, Res,
-        ResMut, With,
+        Bundle, Commands, Component, Entity, Event, EventReader, EventWriter, GlobalTransform,
+        NextState, Parent, Query, Res, ResMut, Single, State, With, Without,
     },
     sprite::Sprite,
 };
-use bevy_ecs_ldtk::{GridCoords, LdtkEntity, LevelSelection};
+use bevy_ecs_ldtk::{
+    assets::{LdtkProject, LevelIndices, LevelMetadataAccessor},
+    ldtk::Level,
+    prelude::{LdtkFields, RawLevelAccessor},
+    EntityInstance, LdtkEntity, LdtkProjectHandle, LevelIid, LevelSelection,
+};
 
-use crate::{
-    impl_entity,
-    player::{Player, SyncCameraWithPlayer},
-    query_as_single,
+use super::{
+    spawn::PendingSpawn,
+    transition::{ActiveTransition, ChangeLevelKind, LevelTransition, LevelTransitionConfig},
+    Entity as _,
 };
+use crate::{impl_entity, player::Player};
 
+/// Starts a level transition. Whatever level is asked for is looked up in the loaded project first,
+/// and ignored with a warning if it doesn't exist.
 #[derive(Event, Debug, Default)]
 pub enum ChangeLevel {
     Level(LevelSelection),
+    /// Enter the level at the [`SpawnPoint`](super::spawn::SpawnPoint) with the given name.
+    LevelAt(LevelSelection, String),
+    ByIdentifier(String),
+    ByIid(String),
+    /// The following level in project order, sending [`CampaignCompleted`] after the last one.
     #[default]
     Next,
+    /// The following level in the current world, wrapping around to its first.
+    NextInWorld,
+    /// The preceding level in project order, staying put on the first one.
+    Previous,
 }
 
+/// Sent instead of a level change when [`ChangeLevel::Next`] is asked for on the last level.
+#[derive(Event, Debug, Default)]
+pub struct CampaignCompleted;
+
 impl ChangeLevel {
     pub fn level(level: LevelSelection) -> Self {
         Self::Level(level)
     }
+
+    pub fn level_at(level: LevelSelection, spawn: impl Into<String>) -> Self {
+        Self::LevelAt(level, spawn.into())
+    }
+
+    pub fn kind(&self) -> ChangeLevelKind {
+        match self {
+            Self::Level(_) => ChangeLevelKind::Level,
+            Self::LevelAt(..) => ChangeLevelKind::LevelAt,
+            Self::ByIdentifier(_) => ChangeLevelKind::ByIdentifier,
+            Self::ByIid(_) => ChangeLevelKind::ByIid,
+            Self::Next => ChangeLevelKind::Next,
+            Self::NextInWorld => ChangeLevelKind::NextInWorld,
+            Self::Previous => ChangeLevelKind::Previous,
+        }
+    }
+}
+
+/// What a [`ChangeLevel`] resolves to against the loaded project.
+enum ResolvedLevel {
+    Level(LevelIndices),
+    CampaignCompleted,
 }
 
-#[derive(Component, Default)]
-pub struct LevelGoal;
+/// Every level of the project in order, along with its indices.
+pub fn level_indices(project: &LdtkProject) -> Vec<(LevelIndices, &Level)> {
+    let data = project.json_data();
+    if data.worlds.is_empty() {
+        data.levels
+            .iter()
+            .enumerate()
+            .map(|(level, raw)| (LevelIndices::in_root(level), raw))
+            .collect()
+    } else {
+        data.worlds
+            .iter()
+            .enumerate()
+            .flat_map(|(world, world_data)| {
+                world_data
+                    .levels
+                    .iter()
+                    .enumerate()
+                    .map(move |(level, raw)| (LevelIndices::in_world(world, level), raw))
+            })
+            .collect()
+    }
+}
+
+fn resolve_level(
+    project: &LdtkProject,
+    current: &LevelSelection,
+    change: &ChangeLevel,
+) -> Option<ResolvedLevel> {
+    let levels = level_indices(project);
+    let find = |matches: &dyn Fn(&Level) -> bool| {
+        levels
+            .iter()
+            .find(|(_, level)| matches(level))
+            .map(|(indices, _)| ResolvedLevel::Level(*indices))
+    };
+
+    let current = raw_level(project, current).and_then(|current| {
+        levels
+            .iter()
+            .position(|(_, level)| level.iid == current.iid)
+    });
+
+    match change {
+        ChangeLevel::Level(selection) | ChangeLevel::LevelAt(selection, _) => {
+            let target = raw_level(project, selection)?;
+            find(&|level| level.iid == target.iid)
+        }
+        ChangeLevel::ByIdentifier(identifier) => find(&|level| &level.identifier == identifier),
+        ChangeLevel::ByIid(iid) => find(&|level| &level.iid == iid),
+        ChangeLevel::Next => match levels.get(current? + 1) {
+            Some((indices, _)) => Some(ResolvedLevel::Level(*indices)),
+            None => Some(ResolvedLevel::CampaignCompleted),
+        },
+        ChangeLevel::NextInWorld => {
+            let (indices, _) = &levels[current?];
+            let in_world: Vec<_> = levels
+                .iter()
+                .filter(|(other, _)| other.world == indices.world)
+                .collect();
+            let position = in_world.iter().position(|(other, _)| other == indices)?;
+            let (next, _) = in_world[(position + 1) % in_world.len()];
+            Some(ResolvedLevel::Level(*next))
+        }
+        ChangeLevel::Previous => {
+            let (indices, _) = levels.get(current?.checked_sub(1)?)?;
+            Some(ResolvedLevel::Level(*indices))
+        }
+    }
+}
+
+/// Finds the level a [`LevelSelection`] points to, whichever form it takes.
+pub fn raw_level<'a>(project: &'a LdtkProject, selection: &LevelSelection) -> Option<&'a Level> {
+    match selection {
+        LevelSelection::Indices(indices) => project.get_raw_level_at_indices(indices),
+        LevelSelection::Iid(iid) => project.get_raw_level_by_iid(iid.get()),
+        LevelSelection::Identifier(identifier) => project
+            .iter_raw_levels()
+            .find(|level| &level.identifier == identifier),
+        LevelSelection::Uid(uid) => project.iter_raw_levels().find(|level| level.uid == *uid),
+    }
+}
+
+/// The selected level along with the entity it was spawned as and its transform, once it has been.
+pub fn selected_level_entity<'a>(
+    project: &'a LdtkProject,
+    selection: &LevelSelection,
+    levels: &'a Query<(Entity, &LevelIid, &GlobalTransform)>,
+) -> Option<(&'a Level, Entity, &'a GlobalTransform)> {
+    let level = raw_level(project, selection)?;
+    let (entity, _, transform) = levels.iter().find(|(_, iid, _)| *iid.get() == level.iid)?;
+    Some((level, entity, transform))
+}
+
+/// Whether an entity parented to `layer` belongs to the level spawned as `level`.
+pub fn on_level_layer(layer: &Parent, level: Entity, layers: &Query<&Parent>) -> bool {
+    layers
+        .get(layer.get())
+        .is_ok_and(|layer_parent| layer_parent.get() == level)
+}
+
+/// The world space rectangle a spawned level covers.
+pub fn level_bounds(level: &Level, transform: &GlobalTransform) -> Rect {
+    // levels are anchored at their bottom left corner.
+    let min = transform.translation().truncate();
+    Rect::from_corners(
+        min,
+        min + Vec2::new(level.px_wid as f32, level.px_hei as f32),
+    )
+}
+
+/// Sends the player on to another level when touched.
+#[derive(Component, Default, Debug)]
+pub struct LevelGoal {
+    /// Identifier of the level to go to, the next one when absent.
+    pub target: Option<String>,
+    /// Name of the [`SpawnPoint`](super::spawn::SpawnPoint) to enter the target level at.
+    pub spawn: Option<String>,
+}
+
+impl LevelGoal {
+    fn from_entity_instance(entity_instance: &EntityInstance) -> Self {
+        Self {
+            target: entity_instance.get_string_field("Level").ok().cloned(),
+            spawn: entity_instance.get_string_field("Spawn").ok().cloned(),
+        }
+    }
+
+    fn change_level(&self) -> ChangeLevel {
+        let Some(target) = &self.target else {
+            return ChangeLevel::Next;
+        };
+
+        match &self.spawn {
+            Some(spawn) => {
+                ChangeLevel::level_at(LevelSelection::Identifier(target.clone()), spawn.clone())
+            }
+            None => ChangeLevel::ByIdentifier(target.clone()),
+        }
+    }
+}
 
 #[derive(Default, Bundle, LdtkEntity)]
 pub(super) struct LevelGoalBundle {
-    _marker: LevelGoal,
+    #[with(LevelGoal::from_entity_instance)]
+    goal: LevelGoal,
     #[cfg(debug_assertions)]
     #[sprite_sheet]
     sprite: Sprite,
-    #[grid_coords]
-    grid_coords: GridCoords,
+    #[with(goal_collider)]
+    collider: Collider,
+    #[with(goal_rigid_body)]
+    rigid_body: RigidBody,
+    sensor: Sensor,
+}
+
+impl_entity!(LevelGoalBundle | "Goal"; 16.);
+
+fn goal_collider(_: &EntityInstance) -> Collider {
+    let (width, height) = LevelGoalBundle::DIMENSIONS.unwrap_or_default();
+    Collider::rectangle(width, height)
 }
 
-impl_entity!(LevelGoalBundle | "LevelGoal"; 16.);
+fn goal_rigid_body(_: &EntityInstance) -> RigidBody {
+    RigidBody::Static
+}
 
+#[allow(clippy::too_many_arguments)]
 pub(super) fn change_level(
+    mut commands: Commands,
     mut event_reader: EventReader<ChangeLevel>,
-    mut level_selection: ResMut<LevelSelection>,
-    mut event_writer: EventWriter<SyncCameraWithPlayer>,
+    level_selection: Res<LevelSelection>,
+    handle: Single<&LdtkProjectHandle>,
+    projects: Res<Assets<LdtkProject>>,
+    transition_state: Res<State<LevelTransition>>,
+    mut next_state: ResMut<NextState<LevelTransition>>,
+    config: Res<LevelTransitionConfig>,
+    mut campaign_completed: EventWriter<CampaignCompleted>,
 ) {
     let Some(level) = event_reader.read().last() else {
         return;
     };
 
-    let LevelSelection::Indices(indices) = level_selection.as_ref() else {
-        warn!("LevelSelection should be of `Indices` form");
+    // one level change at a time, whatever else is asked for meanwhile is dropped.
+    if *transition_state.get() != LevelTransition::Playing {
+        return;
+    }
+
+    let Some(project) = projects.get(&handle.handle) else {
+        warn!("Ignoring {level:?}, the project isn't loaded yet");
         return;
     };
 
-    let new_level = match level {
-        ChangeLevel::Next => LevelSelection::index(indices.level + 1),
-        ChangeLevel::Level(inner) => inner.clone(),
+    // always kept in index form, so that `Next` and `Previous` can count from it.
+    let new_level = match resolve_level(project, &level_selection, level) {
+        Some(ResolvedLevel::Level(indices)) => LevelSelection::Indices(indices),
+        Some(ResolvedLevel::CampaignCompleted) => {
+            campaign_completed.send_default();
+            return;
+        }
+        None => {
+            warn!("Ignoring {level:?}, there is no such level");
+            return;
+        }
+    };
+
+    let spawn = match level {
+        ChangeLevel::LevelAt(_, spawn) => PendingSpawn::Named(spawn.clone()),
+        _ => PendingSpawn::Default,
     };
 
-    *level_selection = new_level;
-    event_writer.send_default();
+    let kind = level.kind();
+    commands.insert_resource(ActiveTransition::new(
+        kind,
+        config.style(kind),
+        new_level,
+        spawn,
+    ));
+    next_state.set(LevelTransition::FadingOut);
 }
 
-// Reminder: change level_goal to Option<T> possibly.
 pub(super) fn transition_level(
-    player: Query<&GridCoords, With<Player>>,
-    level_goal: Query<&GridCoords, With<LevelGoal>>, // could be cached
-    level_selection: Res<LevelSelection>,
+    mut collisions: EventReader<CollisionStarted>,
+    player: Query<(), With<Player>>,
+    goals: Query<&LevelGoal>,
     mut change_level: EventWriter<ChangeLevel>,
-    mut sent_once: Local<bool>,
 ) {
-    *sent_once = !level_selection.is_changed();
-    query_as_single!(player; player);
-    query_as_single!(level_goal; level_goal);
+    for CollisionStarted(a, b) in collisions.read() {
+        let goal = if player.contains(*a) {
+            goals.get(*b)
+        } else if player.contains(*b) {
+            goals.get(*a)
+        } else {
+            continue;
+        };
+        let Ok(goal) = goal else {
+            continue;
+        };
+
+        change_level.send(goal.change_level());
+        // touching two goals at once shouldn't skip a level.
+        return;
+    }
+}
+
+/// Selects whichever level the player is standing in, so walking off an edge carries on into the
+/// neighbouring level without a transition.
+pub(super) fn select_level_under_player(
+    player: Query<&GlobalTransform, With<Player>>,
+    levels: Query<(&LevelIid, &GlobalTransform), Without<Player>>,
+    handle: Single<&LdtkProjectHandle>,
+    projects: Res<Assets<LdtkProject>>,
+    pending_spawn: Res<PendingSpawn>,
+    mut level_selection: ResMut<LevelSelection>,
+) {
+    // the player is still wherever the previous level left them until they've been spawned.
+    if *pending_spawn != PendingSpawn::None {
+        return;
+    }
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    let Some(project) = projects.get(&handle.handle) else {
+        return;
+    };
+    let player = player.translation().truncate();
+
+    let selected = raw_level(project, &level_selection).map(|level| level.iid.as_str());
+    let contains_player = |iid: &LevelIid, transform: &GlobalTransform| {
+        let level = project.get_raw_level_by_iid(iid.get())?;
+        level_bounds(level, transform)
+            .contains(player)
+            .then_some(level)
+    };
 
-    let player = GridCoords::new(player.x, player.y - 1); // because the player doesn't fit on the normal grid
+    if levels.iter().any(|(iid, transform)| {
+        Some(iid.get().as_str()) == selected && contains_player(iid, transform).is_some()
+    }) {
+        return;
+    }
 
-    if &player != level_goal || !*sent_once {
+    let Some(level) = levels
+        .iter()
+        .find_map(|(iid, transform)| contains_player(iid, transform))
+    else {
         return;
+    };
+
+    // kept in index form, `ChangeLevel::Next` counts from it.
+    if let Some((indices, _)) = level_indices(project)
+        .into_iter()
+        .find(|(_, other)| other.iid == level.iid)
+    {
+        *level_selection = LevelSelection::Indices(indices);
     }
-    change_level.send_default();
-    *sent_once = true;
 }
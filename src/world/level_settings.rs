@@ -1,57 +1,233 @@
+use std::fmt;
+
+use avian2d::prelude::Gravity;
 use bevy::{
-    asset::Assets,
-    prelude::{DetectChanges, Res, ResMut, Resource, Single},
+    asset::{AssetEvent, Assets},
+    color::Color,
+    log::error,
+    math::{IVec2, Vec2},
+    prelude::{DetectChanges, Event, EventReader, EventWriter, Res, ResMut, Resource, Single},
 };
 use bevy_ecs_ldtk::{
-    assets::{LdtkProject, LevelMetadataAccessor},
+    assets::LdtkProject,
     ldtk::{FieldInstance, FieldValue},
-    prelude::RawLevelAccessor,
     LdtkProjectHandle, LevelSelection,
 };
-use derive_more::derive::Deref;
 
-trait FromFieldInstances: FromFieldValue {
-    const IDENTIFIER: &'static str;
+use super::{level::raw_level, GRAVITY};
+use crate::level_settings;
 
-    fn from_field_instances(f: &Vec<FieldInstance>) -> Option<Self> {
-        Self::from_field_value(
-            f.iter()
-                .find(|e| e.identifier == Self::IDENTIFIER)?
-                .value
-                .clone(),
-        )
-    }
+/// Describes a level field read by [`LevelSettings`], used to validate LDtk projects.
+#[derive(Debug, Clone, Copy)]
+pub struct LevelFieldInfo {
+    pub identifier: &'static str,
+    /// The field's type as LDtk spells it, e.g. `Bool` or `Float`.
+    pub field_type: &'static str,
+    /// Optional fields only override defaults, levels don't need to declare them.
+    pub optional: bool,
 }
 
-trait FromFieldValue: Sized {
-    fn from_field_value(val: FieldValue) -> Option<Self>;
+/// Why a level field couldn't be read. The affected setting falls back to its default.
+#[derive(Debug, Clone)]
+pub enum LevelFieldError {
+    Missing {
+        identifier: &'static str,
+    },
+    WrongType {
+        identifier: &'static str,
+        expected: &'static str,
+        // boxed, field values are large and errors are rare.
+        found: Box<FieldValue>,
+    },
+}
+
+impl fmt::Display for LevelFieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing { identifier } => write!(f, "level field `{identifier}` is missing"),
+            Self::WrongType {
+                identifier,
+                expected,
+                found,
+            } => write!(
+                f,
+                "level field `{identifier}` should be {expected}, found {found:?}"
+            ),
+        }
+    }
 }
 
-#[derive(Resource, Default, Debug)]
-pub struct LevelSettings {
-    pub camera_follow: CameraFollow,
+impl std::error::Error for LevelFieldError {}
+
+/// A [`FieldValue`] of a different type than the one being read.
+#[derive(Debug, Clone)]
+pub struct UnexpectedFieldValue(pub FieldValue);
+
+#[derive(Event, Debug, Clone)]
+pub enum LevelSettingsError {
+    LevelNotFound(LevelSelection),
+    Field(LevelFieldError),
 }
 
-impl LevelSettings {
-    pub fn from_field_instances(fi: &Vec<FieldInstance>) -> Self {
-        Self {
-            camera_follow: CameraFollow::from_field_instances(&fi).unwrap(),
+impl fmt::Display for LevelSettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LevelNotFound(selection) => write!(f, "no level matches {selection:?}"),
+            Self::Field(e) => e.fmt(f),
         }
     }
 }
 
-#[derive(Default, Debug, PartialEq, Eq, Deref)]
-pub struct CameraFollow(pub bool);
-impl FromFieldInstances for CameraFollow {
-    const IDENTIFIER: &'static str = "CameraFollow";
-}
+pub trait FromFieldInstances: FromFieldValue + Default {
+    const IDENTIFIER: &'static str;
+    const INFO: LevelFieldInfo = LevelFieldInfo {
+        identifier: Self::IDENTIFIER,
+        field_type: Self::FIELD_TYPE,
+        optional: Self::NULLABLE,
+    };
 
-impl FromFieldValue for CameraFollow {
-    fn from_field_value(val: FieldValue) -> Option<Self> {
-        let FieldValue::Bool(v) = val else {
-            return None;
+    fn from_field_instances(f: &[FieldInstance]) -> Result<Self, LevelFieldError> {
+        let Some(field) = f.iter().find(|e| e.identifier == Self::IDENTIFIER) else {
+            if Self::NULLABLE {
+                return Ok(Self::default());
+            }
+            return Err(LevelFieldError::Missing {
+                identifier: Self::IDENTIFIER,
+            });
         };
-        Some(Self(v))
+
+        Self::from_field_value(field.value.clone()).map_err(|UnexpectedFieldValue(found)| {
+            LevelFieldError::WrongType {
+                identifier: Self::IDENTIFIER,
+                expected: Self::FIELD_TYPE,
+                found: Box::new(found),
+            }
+        })
+    }
+
+    /// Reads the field, recording the error and falling back to the default if that fails.
+    fn from_field_instances_or_default(
+        f: &[FieldInstance],
+        errors: &mut Vec<LevelFieldError>,
+    ) -> Self {
+        Self::from_field_instances(f).unwrap_or_else(|e| {
+            errors.push(e);
+            Self::default()
+        })
+    }
+}
+
+pub trait FromFieldValue: Sized {
+    const FIELD_TYPE: &'static str;
+    /// Whether LDtk may leave the field out or set it to null.
+    const NULLABLE: bool = false;
+
+    fn from_field_value(val: FieldValue) -> Result<Self, UnexpectedFieldValue>;
+}
+
+macro_rules! impl_from_field_value {
+    // fields LDtk never leaves null.
+    ($t:ty: $field_type:literal, $variant:ident) => {
+        impl FromFieldValue for $t {
+            const FIELD_TYPE: &'static str = $field_type;
+
+            fn from_field_value(val: FieldValue) -> Result<Self, UnexpectedFieldValue> {
+                match val {
+                    FieldValue::$variant(v) => Ok(v),
+                    val => Err(UnexpectedFieldValue(val)),
+                }
+            }
+        }
+    };
+
+    // nullable fields, read as `Option<T>` or as `T` when null isn't acceptable.
+    ($t:ty: $field_type:literal, $variant:ident?) => {
+        impl FromFieldValue for $t {
+            const FIELD_TYPE: &'static str = $field_type;
+
+            fn from_field_value(val: FieldValue) -> Result<Self, UnexpectedFieldValue> {
+                match val {
+                    FieldValue::$variant(Some(v)) => Ok(v),
+                    val => Err(UnexpectedFieldValue(val)),
+                }
+            }
+        }
+
+        impl FromFieldValue for Option<$t> {
+            const FIELD_TYPE: &'static str = $field_type;
+            const NULLABLE: bool = true;
+
+            fn from_field_value(val: FieldValue) -> Result<Self, UnexpectedFieldValue> {
+                match val {
+                    FieldValue::$variant(v) => Ok(v),
+                    val => Err(UnexpectedFieldValue(val)),
+                }
+            }
+        }
+    };
+
+    // arrays of nullable values, where a single null rejects the whole array.
+    (Vec<$t:ty>: $field_type:literal, $variant:ident[?]) => {
+        impl FromFieldValue for Vec<$t> {
+            const FIELD_TYPE: &'static str = $field_type;
+
+            fn from_field_value(val: FieldValue) -> Result<Self, UnexpectedFieldValue> {
+                match &val {
+                    FieldValue::$variant(v) => v.iter().cloned().collect(),
+                    _ => None,
+                }
+                .ok_or(UnexpectedFieldValue(val))
+            }
+        }
+    };
+}
+
+impl_from_field_value!(bool: "Bool", Bool);
+impl_from_field_value!(Color: "Color", Color);
+impl_from_field_value!(i32: "Int", Int?);
+impl_from_field_value!(f32: "Float", Float?);
+impl_from_field_value!(String: "String", String?);
+impl_from_field_value!(IVec2: "Point", Point?);
+impl_from_field_value!(Vec<bool>: "Array<Bool>", Bools);
+impl_from_field_value!(Vec<Color>: "Array<Color>", Colors);
+impl_from_field_value!(Vec<i32>: "Array<Int>", Ints[?]);
+impl_from_field_value!(Vec<f32>: "Array<Float>", Floats[?]);
+impl_from_field_value!(Vec<String>: "Array<String>", Strings[?]);
+impl_from_field_value!(Vec<IVec2>: "Array<Point>", Points[?]);
+
+level_settings! {
+    #[derive(Resource, Default, Debug)]
+    pub struct LevelSettings {
+        /// Overrides [`PlayerMovementConfig::coyote_time`](crate::player::PlayerMovementConfig) for this level.
+        coyote_time: CoyoteTime(Option<f32>) = "CoyoteTime",
+        /// Overrides [`PlayerMovementConfig::jump_buffer_time`](crate::player::PlayerMovementConfig) for this level.
+        jump_buffer_time: JumpBufferTime(Option<f32>) = "JumpBufferTime",
+        /// Replaces the horizontal component of [`GRAVITY`](super::GRAVITY) while in this level.
+        gravity_x: GravityX(Option<f32>) = "GravityX",
+        /// Replaces the vertical component of [`GRAVITY`](super::GRAVITY) while in this level.
+        gravity_y: GravityY(Option<f32>) = "GravityY",
+        /// Scales the player's walking and running speed, e.g. below `1.` for underwater levels.
+        speed_multiplier: SpeedMultiplier(Option<f32>) = "SpeedMultiplier",
+        /// Scales how quickly the player reaches their target speed, e.g. well below `1.` for ice.
+        acceleration_multiplier: AccelerationMultiplier(Option<f32>) = "AccelerationMultiplier",
+        /// Overrides [`PlayerMovementConfig::jump_height`](crate::player::PlayerMovementConfig) for this level.
+        jump_height: JumpHeight(Option<f32>) = "JumpHeight",
+        /// The `x;y` grid cell the player enters the level at when no named spawn point was asked for.
+        spawnpoint: Spawnpoint(Option<String>) = "Spawnpoint",
+        /// Top left grid cell the camera may show, instead of the level's own corner.
+        camera_bounds_min: CameraBoundsMin(Option<IVec2>) = "CameraBoundsMin",
+        /// Bottom right grid cell the camera may show, instead of the level's own corner.
+        camera_bounds_max: CameraBoundsMax(Option<IVec2>) = "CameraBoundsMax",
+        /// Overrides [`CameraController::smoothing`](crate::render::camera::CameraController) for this level.
+        camera_smoothing: CameraSmoothing(Option<f32>) = "CameraSmoothing",
+        /// Overrides the horizontal half extent of [`CameraController::deadzone`](crate::render::camera::CameraController).
+        camera_deadzone_x: CameraDeadzoneX(Option<f32>) = "CameraDeadzoneX",
+        /// Overrides the vertical half extent of [`CameraController::deadzone`](crate::render::camera::CameraController).
+        camera_deadzone_y: CameraDeadzoneY(Option<f32>) = "CameraDeadzoneY",
+        /// Overrides [`CameraController::look_ahead`](crate::render::camera::CameraController) for this level.
+        camera_look_ahead: CameraLookAhead(Option<f32>) = "CameraLookAhead",
+        /// Overrides [`CameraController::fall_bias`](crate::render::camera::CameraController) for this level.
+        camera_fall_bias: CameraFallBias(Option<f32>) = "CameraFallBias",
     }
 }
 
@@ -60,19 +236,52 @@ pub(super) fn update_level_settings(
     level_selection: Res<LevelSelection>,
     handle: Single<&LdtkProjectHandle>,
     projects: Res<Assets<LdtkProject>>,
+    mut project_events: EventReader<AssetEvent<LdtkProject>>,
+    mut errors: EventWriter<LevelSettingsError>,
 ) {
-    if !level_selection.is_changed() {
+    // the selection may change before the project has loaded, so loading it counts as a change too.
+    let mut project_loaded = false;
+    for event in project_events.read() {
+        if let AssetEvent::Added { id } | AssetEvent::Modified { id } = event {
+            project_loaded |= *id == handle.handle.id();
+        }
+    }
+
+    if !level_selection.is_changed() && !project_loaded {
         return;
     }
 
-    let project: &LdtkProject = projects.get(&handle.handle).expect("project should exist");
+    let Some(project) = projects.get(&handle.handle) else {
+        return;
+    };
+
+    let Some(level) = raw_level(project, &level_selection) else {
+        let e = LevelSettingsError::LevelNotFound(level_selection.clone());
+        error!("{e}");
+        errors.send(e);
+        return;
+    };
+
+    let (settings, field_errors) = LevelSettings::from_field_instances(&level.field_instances);
+    *level_settings = settings;
 
-    let level = match *level_selection {
-        LevelSelection::Indices(ref idx) => project.get_raw_level_at_indices(dbg!(&idx)),
-        LevelSelection::Iid(ref iid) => project.get_raw_level_by_iid(dbg!(iid.get())),
-        _ => todo!(),
+    for e in field_errors {
+        error!("In level `{}`: {e}", level.identifier);
+        errors.send(LevelSettingsError::Field(e));
+    }
+}
+
+pub(super) fn apply_level_gravity(
+    level_settings: Res<LevelSettings>,
+    mut gravity: ResMut<Gravity>,
+) {
+    if !level_settings.is_changed() {
+        return;
     }
-    .expect("level should exist");
 
-    *level_settings = LevelSettings::from_field_instances(&level.field_instances);
+    // levels without overrides fall back to the default, undoing the previous level's.
+    gravity.0 = Vec2::new(
+        level_settings.gravity_x.unwrap_or(GRAVITY.0.x),
+        level_settings.gravity_y.unwrap_or(GRAVITY.0.y),
+    );
 }
@@ -1,24 +1,54 @@
+pub mod camera_zone;
+pub mod checkpoint;
+pub mod collision;
+pub mod death;
+pub mod hazard;
 pub mod level;
 pub mod level_settings;
+pub mod platform;
+pub mod selection;
+pub mod spawn;
+pub mod transition;
+pub mod validation;
 
-use avian2d::prelude::{Collider, Gravity, RigidBody};
+use avian2d::prelude::{
+    CoefficientCombine, Friction, Gravity, PhysicsSet, PostProcessCollisions, Restitution,
+};
 use bevy::{
-    app::{plugin_group, Plugin, Startup, Update},
-    asset::AssetServer,
+    app::{plugin_group, FixedUpdate, Plugin, Update},
     math::Vec2,
-    prelude::{Bundle, Commands, Component, IntoSystemConfigs, Res},
+    prelude::{in_state, AppExtStates, Bundle, Component, IntoSystemConfigs},
     utils::default,
 };
 use bevy_ecs_ldtk::{
-    app::LdtkIntCell, LdtkSettings, LdtkWorldBundle, LevelSelection, LevelSpawnBehavior,
+    app::LdtkIntCell, IntGridRendering, LdtkSettings, LevelSelection, LevelSpawnBehavior,
+};
+use camera_zone::{enter_camera_zone, ActiveCameraZone, CameraZoneBundle};
+use checkpoint::{activate_checkpoint, CheckpointActivated, CheckpointBundle};
+use collision::{merge_solid_cells, SolidCell};
+use death::{
+    player_alive, run_death_sequence, DeathSequence, DeathSequenceConfig, PlayerDied,
+    PlayerRespawned, RespawnPoint,
+};
+use hazard::{fall_out_of_level, touch_hazard, LavaBundle, PitBundle, SpikesBundle};
+use level::{
+    change_level, select_level_under_player, CampaignCompleted, ChangeLevel, LevelGoalBundle,
+};
+use level_settings::{
+    apply_level_gravity, update_level_settings, LevelSettings, LevelSettingsError,
 };
-use level::{change_level, ChangeLevel, LevelGoalBundle};
-use level_settings::{update_level_settings, LevelSettings};
+use platform::{
+    filter_one_way_platforms, move_platforms, MovingPlatformBundle, OneWayPlatformBundle,
+};
+use selection::{
+    change_world, report_world_load_failure, spawn_world, ChangeWorld, WorldSelection,
+};
+use spawn::{spawn_player, PendingSpawn, SpawnPointBundle};
+use transition::{fade_in, fade_out, wait_for_level, LevelTransition, LevelTransitionConfig};
+use validation::{validate_project, LdtkRegistry, LdtkValidationReport};
 
 use crate::{impl_intcell, utils::LdtkAppTraitExt};
 
-static WORLD_PATH: &'static str = "world.ldtk";
-
 pub const GRAVITY: Gravity = Gravity(Vec2::new(0., -98.1));
 
 plugin_group! {
@@ -34,34 +64,88 @@ impl Plugin for BasePlugin {
     fn build(&self, app: &mut bevy::app::App) {
         app.insert_resource(GRAVITY)
             .init_resource::<LevelSettings>()
+            .init_resource::<LdtkRegistry>()
+            .init_resource::<LdtkValidationReport>()
+            .insert_resource(WorldSelection::resolve())
             .insert_resource(LevelSelection::index(0))
+            .insert_resource(PendingSpawn::Default)
+            .init_resource::<RespawnPoint>()
+            .init_resource::<DeathSequence>()
+            .init_resource::<DeathSequenceConfig>()
+            .init_resource::<LevelTransitionConfig>()
+            .init_resource::<ActiveCameraZone>()
+            .init_state::<LevelTransition>()
             .insert_resource(LdtkSettings {
                 level_spawn_behavior: LevelSpawnBehavior::UseWorldTranslation {
                     load_level_neighbors: true,
                 },
+                // border walls have no tiles and are meant to be invisible, terrain is drawn by its auto layer.
+                int_grid_rendering: IntGridRendering::Invisible,
                 ..default()
             })
             .add_event::<ChangeLevel>()
+            .add_event::<CampaignCompleted>()
+            .add_event::<ChangeWorld>()
+            .add_event::<LevelSettingsError>()
+            .add_event::<CheckpointActivated>()
+            .add_event::<PlayerDied>()
+            .add_event::<PlayerRespawned>()
             .register_ldtk_int_cell::<GrassTerrainBundle>()
+            .register_ldtk_int_cell::<BorderWallBundle>()
+            .register_ldtk_int_cell::<SpikesBundle>()
+            .register_ldtk_int_cell::<LavaBundle>()
+            .register_ldtk_int_cell::<PitBundle>()
+            .register_ldtk_int_cell::<OneWayPlatformBundle>()
             .register_ldtk_entity::<LevelGoalBundle>()
+            .register_ldtk_entity::<SpawnPointBundle>()
+            .register_ldtk_entity::<CheckpointBundle>()
+            .register_ldtk_entity::<MovingPlatformBundle>()
+            .register_ldtk_entity::<CameraZoneBundle>()
+            .add_systems(PostProcessCollisions, filter_one_way_platforms)
             .add_systems(
-                Update,
-                ((change_level, level::transition_level, update_level_settings).chain()),
+                FixedUpdate,
+                move_platforms.before(PhysicsSet::StepSimulation),
             )
-            .add_systems(Startup, spawn_world);
-    }
-}
+            .add_systems(
+                Update,
+                (
+                    (change_world, spawn_world).chain(),
+                    report_world_load_failure,
+                    validate_project,
+                    merge_solid_cells,
+                    (
+                        change_level,
+                        level::transition_level,
+                        select_level_under_player.run_if(in_state(LevelTransition::Playing)),
+                        update_level_settings,
+                        apply_level_gravity,
+                        spawn_player,
+                        activate_checkpoint,
+                        enter_camera_zone,
+                        (touch_hazard, fall_out_of_level)
+                            .run_if(in_state(LevelTransition::Playing))
+                            .run_if(player_alive),
+                        run_death_sequence,
+                    )
+                        .chain(),
+                    fade_out.run_if(in_state(LevelTransition::FadingOut)),
+                    wait_for_level
+                        .after(spawn_player)
+                        .run_if(in_state(LevelTransition::Loading)),
+                    fade_in.run_if(in_state(LevelTransition::FadingIn)),
+                ),
+            );
 
-fn spawn_world(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn(LdtkWorldBundle {
-        ldtk_handle: asset_server.load(WORLD_PATH).into(),
-        ..default()
-    });
+        #[cfg(debug_assertions)]
+        app.add_systems(Update, platform::draw_platform_paths);
+    }
 }
 
 pub trait IntCell {
     const DIMENSIONS: (f32, f32);
     const INTCELL_ID: i32;
+    /// Only bind the value on the IntGrid layer with this identifier, any layer otherwise.
+    const LAYER: Option<&str> = None;
 }
 
 pub trait Entity {
@@ -75,21 +159,45 @@ pub struct GrassTerrain;
 #[derive(Default, Bundle)]
 pub struct GrassTerrainBundle {
     _m: GrassTerrain,
-    collider: Collider,
-    rigidbody: RigidBody,
+    // colliders are merged per layer, see `collision::merge_solid_cells`.
+    solid: SolidCell,
 }
 
-impl_intcell!(GrassTerrainBundle | 1; 16.);
+impl_intcell!(GrassTerrainBundle | "Terrain": 1; 19.);
 
 impl LdtkIntCell for GrassTerrainBundle {
+    fn bundle_int_cell(
+        _int_grid_cell: bevy_ecs_ldtk::IntGridCell,
+        _layer_instance: &bevy_ecs_ldtk::prelude::LayerInstance,
+    ) -> Self {
+        default()
+    }
+}
+
+/// Keeps the player inside the world. Frictionless so that pushing against one doesn't stop a fall.
+#[derive(Component, Default)]
+pub struct BorderWall;
+
+#[derive(Bundle)]
+pub struct BorderWallBundle {
+    _m: BorderWall,
+    solid: SolidCell,
+}
+
+impl_intcell!(BorderWallBundle | "BorderWalls": 1; 19.);
+
+impl LdtkIntCell for BorderWallBundle {
     fn bundle_int_cell(
         _int_grid_cell: bevy_ecs_ldtk::IntGridCell,
         _layer_instance: &bevy_ecs_ldtk::prelude::LayerInstance,
     ) -> Self {
         Self {
-            collider: Collider::rectangle(Self::DIMENSIONS.0, Self::DIMENSIONS.1),
-            rigidbody: RigidBody::Static,
-            ..default()
+            _m: BorderWall,
+            solid: SolidCell {
+                friction: Friction::ZERO.with_combine_rule(CoefficientCombine::Min),
+                restitution: Restitution::ZERO,
+                ..default()
+            },
         }
     }
 }
@@ -0,0 +1,310 @@
+use avian2d::prelude::{Collider, Collisions, LinearVelocity, RigidBody};
+use bevy::{
+    math::{IVec2, Vec2},
+    prelude::{Bundle, Component, Entity, Query, Res, ResMut, Transform, With, Without},
+    sprite::Sprite,
+    time::Time,
+    utils::{default, HashSet},
+};
+use bevy_ecs_ldtk::{
+    app::{LdtkEntity, LdtkIntCell},
+    prelude::LdtkFields,
+    utils::ldtk_pixel_coords_to_translation_pivoted,
+    EntityInstance,
+};
+
+use super::{collision::SolidCell, level_settings::FromFieldValue};
+use crate::{impl_entity, impl_intcell, level_enum};
+
+/// Solid from above only. Spawned on the merged collider of [`OneWayPlatformBundle`] cells.
+#[derive(Component, Default, Debug)]
+pub struct OneWayPlatform {
+    /// Bodies currently passing through, they're let through until they stop overlapping.
+    passing: HashSet<Entity>,
+}
+
+/// How a body treats [`OneWayPlatform`]s, `ByNormal` when absent.
+#[derive(Component, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PassThroughOneWayPlatform {
+    /// Pass through from below, stand on from above.
+    #[default]
+    ByNormal,
+    Always,
+    Never,
+}
+
+#[derive(Bundle)]
+pub struct OneWayPlatformBundle {
+    solid: SolidCell,
+}
+
+impl_intcell!(OneWayPlatformBundle | "Terrain": 5; 19.);
+
+impl LdtkIntCell for OneWayPlatformBundle {
+    fn bundle_int_cell(
+        _int_grid_cell: bevy_ecs_ldtk::IntGridCell,
+        _layer_instance: &bevy_ecs_ldtk::prelude::LayerInstance,
+    ) -> Self {
+        Self {
+            solid: SolidCell {
+                one_way: true,
+                ..default()
+            },
+        }
+    }
+}
+
+// runs between the narrow phase and the solver, dropping contacts the platform shouldn't resolve.
+pub(super) fn filter_one_way_platforms(
+    mut platforms: Query<&mut OneWayPlatform>,
+    bodies: Query<Option<&PassThroughOneWayPlatform>, (With<Collider>, Without<OneWayPlatform>)>,
+    mut collisions: ResMut<Collisions>,
+) {
+    collisions.retain(|contacts| {
+        let (mut platform, other, platform_is_first) =
+            if let Ok(platform) = platforms.get_mut(contacts.entity1) {
+                (platform, contacts.entity2, true)
+            } else if let Ok(platform) = platforms.get_mut(contacts.entity2) {
+                (platform, contacts.entity1, false)
+            } else {
+                return true;
+            };
+
+        let penetrating = contacts
+            .manifolds
+            .iter()
+            .any(|manifold| manifold.contacts.iter().any(|c| c.penetration > 0.));
+
+        if platform.passing.contains(&other) {
+            if penetrating {
+                return false;
+            }
+            platform.passing.remove(&other);
+        }
+
+        match bodies.get(other) {
+            Ok(Some(PassThroughOneWayPlatform::Never)) => true,
+            Ok(Some(PassThroughOneWayPlatform::Always)) => {
+                platform.passing.insert(other);
+                false
+            }
+            _ => {
+                // only keep contacts pushing the body up and out of the platform's top.
+                let from_above = contacts.manifolds.iter().all(|manifold| {
+                    let normal = if platform_is_first {
+                        manifold.normal1
+                    } else {
+                        manifold.normal2
+                    };
+                    normal.length() > f32::EPSILON && normal.dot(Vec2::Y) >= 0.5
+                });
+                if !from_above {
+                    platform.passing.insert(other);
+                }
+                from_above
+            }
+        }
+    });
+}
+
+level_enum! {
+    /// What a [`MovingPlatform`] does once it reaches the end of its path.
+    #[derive(Default)]
+    pub enum PathMode {
+        /// Turn around and go back the way it came.
+        #[default]
+        PingPong,
+        /// Head straight back to the first point.
+        Loop,
+        /// Stop at the last point.
+        Once,
+    }
+}
+
+/// A kinematic platform following the `Path` points of its LDtk entity. It moves by velocity only, so
+/// tnua carries whoever stands on it along.
+#[derive(Component, Debug, Default)]
+pub struct MovingPlatform {
+    /// In the space of the platform's layer, starting at the platform's own position.
+    pub path: Vec<Vec2>,
+    pub speed: f32,
+    /// Seconds spent standing still at every point.
+    pub wait: f32,
+    pub mode: PathMode,
+    target: usize,
+    backwards: bool,
+    waiting: f32,
+}
+
+impl MovingPlatform {
+    fn from_entity_instance(
+        entity_instance: &EntityInstance,
+        layer_instance: &bevy_ecs_ldtk::prelude::LayerInstance,
+    ) -> Self {
+        let grid_size = layer_instance.grid_size;
+        let layer_height = layer_instance.c_hei * grid_size;
+        let size = IVec2::new(entity_instance.width, entity_instance.height);
+        // where inside its cell the entity sits, kept for every point of the path.
+        let cell_offset = entity_instance.px - entity_instance.grid * grid_size;
+        let translation_at = |px: IVec2| {
+            ldtk_pixel_coords_to_translation_pivoted(px, layer_height, size, entity_instance.pivot)
+        };
+
+        let points: Vec<IVec2> = entity_instance
+            .get_maybe_points_field("Path")
+            .map(|points| points.iter().flatten().copied().collect())
+            .unwrap_or_default();
+
+        let path = std::iter::once(translation_at(entity_instance.px))
+            .chain(
+                points
+                    .into_iter()
+                    .map(|point| translation_at(point * grid_size + cell_offset)),
+            )
+            .collect();
+
+        let mode = entity_instance
+            .get_field_instance("Mode")
+            .ok()
+            .and_then(|field| PathMode::from_field_value(field.value.clone()).ok())
+            .unwrap_or_default();
+
+        Self {
+            path,
+            speed: entity_instance
+                .get_float_field("Speed")
+                .copied()
+                .unwrap_or(30.),
+            wait: entity_instance
+                .get_float_field("Wait")
+                .copied()
+                .unwrap_or(0.5),
+            mode,
+            target: 1,
+            ..default()
+        }
+    }
+
+    /// Picks the point after the current target, `false` once a [`PathMode::Once`] path is done.
+    fn advance(&mut self) -> bool {
+        let last = self.path.len() - 1;
+        match self.mode {
+            PathMode::Loop => self.target = (self.target + 1) % self.path.len(),
+            PathMode::Once if self.target == last => return false,
+            PathMode::Once => self.target += 1,
+            PathMode::PingPong => {
+                if self.target == last {
+                    self.backwards = true;
+                } else if self.target == 0 {
+                    self.backwards = false;
+                }
+                self.target = if self.backwards {
+                    self.target - 1
+                } else {
+                    self.target + 1
+                };
+            }
+        }
+        true
+    }
+}
+
+#[derive(Bundle)]
+pub(super) struct MovingPlatformBundle {
+    platform: MovingPlatform,
+    collider: Collider,
+    rigid_body: RigidBody,
+    velocity: LinearVelocity,
+    sprite: Sprite,
+}
+
+impl_entity!(MovingPlatformBundle | "MovingPlatform");
+
+impl LdtkEntity for MovingPlatformBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        layer_instance: &bevy_ecs_ldtk::prelude::LayerInstance,
+        tileset: Option<&bevy::prelude::Handle<bevy::prelude::Image>>,
+        tileset_definition: Option<&bevy_ecs_ldtk::prelude::TilesetDefinition>,
+        _asset_server: &bevy::prelude::AssetServer,
+        texture_atlases: &mut bevy::prelude::Assets<bevy::prelude::TextureAtlasLayout>,
+    ) -> Self {
+        Self {
+            platform: MovingPlatform::from_entity_instance(entity_instance, layer_instance),
+            collider: Collider::rectangle(
+                entity_instance.width as f32,
+                entity_instance.height as f32,
+            ),
+            rigid_body: RigidBody::Kinematic,
+            velocity: default(),
+            sprite: bevy_ecs_ldtk::utils::sprite_sheet_from_entity_info(
+                entity_instance,
+                tileset,
+                tileset_definition,
+                texture_atlases,
+                true,
+            ),
+        }
+    }
+}
+
+pub(super) fn move_platforms(
+    mut platforms: Query<(&mut MovingPlatform, &Transform, &mut LinearVelocity)>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_secs();
+    if delta <= 0. {
+        return;
+    }
+
+    for (mut platform, transform, mut velocity) in &mut platforms {
+        if platform.path.len() < 2 || platform.target >= platform.path.len() {
+            *velocity = LinearVelocity::ZERO;
+            continue;
+        }
+
+        if platform.waiting > 0. {
+            platform.waiting -= delta;
+            *velocity = LinearVelocity::ZERO;
+            continue;
+        }
+
+        let to_target = platform.path[platform.target] - transform.translation.truncate();
+        let step = platform.speed * delta;
+
+        if to_target.length() > step {
+            velocity.0 = to_target.normalize() * platform.speed;
+            continue;
+        }
+
+        // land exactly on the point instead of overshooting it.
+        velocity.0 = to_target / delta;
+        platform.waiting = platform.wait;
+        if !platform.advance() {
+            platform.target = platform.path.len();
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+pub(super) fn draw_platform_paths(
+    mut gizmos: bevy::prelude::Gizmos<avian2d::prelude::PhysicsGizmos>,
+    platforms: Query<(&MovingPlatform, &Transform, &bevy::prelude::GlobalTransform)>,
+) {
+    use bevy::{color::palettes::css::ORANGE, math::Isometry2d};
+
+    for (platform, transform, global_transform) in &platforms {
+        // paths are relative to the layer, which only ever translates.
+        let origin = global_transform.translation().truncate() - transform.translation.truncate();
+        let points = platform.path.iter().map(|point| origin + *point);
+
+        if platform.mode == PathMode::Loop {
+            gizmos.linestrip_2d(points.clone().chain(points.clone().take(1)), ORANGE);
+        } else {
+            gizmos.linestrip_2d(points.clone(), ORANGE);
+        }
+        for point in points {
+            gizmos.circle_2d(Isometry2d::from_translation(point), 2., ORANGE);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use std::{env, fs, path::Path};
+
+use bevy::{
+    asset::{AssetLoadFailedEvent, AssetServer},
+    hierarchy::DespawnRecursiveExt,
+    log::{error, info, warn},
+    prelude::{
+        Commands, DetectChanges, Entity, Event, EventReader, Query, Res, ResMut, Resource, With,
+    },
+    utils::default,
+};
+use bevy_ecs_ldtk::{assets::LdtkProject, LdtkProjectHandle, LdtkWorldBundle, LevelSelection};
+use serde::Deserialize;
+
+use super::spawn::PendingSpawn;
+
+static DEFAULT_WORLD_PATH: &str = "scenes/test-world.ldtk";
+static WORLD_CONFIG_PATH: &str = "config/world.json";
+static WORLD_ENV_VAR: &str = "AWBH_WORLD";
+static WORLD_ARG: &str = "--world";
+
+/// The LDtk project to load, as a path relative to `assets/`.
+///
+/// Resolved from, in order, the `--world <path>` argument, the `AWBH_WORLD` environment variable,
+/// `config/world.json` and finally the bundled test world.
+#[derive(Resource, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WorldSelection {
+    pub path: String,
+}
+
+impl Default for WorldSelection {
+    fn default() -> Self {
+        Self {
+            path: DEFAULT_WORLD_PATH.to_owned(),
+        }
+    }
+}
+
+impl WorldSelection {
+    pub fn resolve() -> Self {
+        if let Some(path) = Self::from_args() {
+            return Self { path };
+        }
+
+        if let Ok(path) = env::var(WORLD_ENV_VAR) {
+            return Self { path };
+        }
+
+        match fs::read(Path::new(WORLD_CONFIG_PATH)) {
+            Ok(contents) => match serde_json::from_slice(&contents) {
+                Ok(selection) => return selection,
+                Err(e) => warn!("Ignoring malformed {WORLD_CONFIG_PATH}: {e}"),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (),
+            Err(e) => warn!("Could not read {WORLD_CONFIG_PATH}: {e}"),
+        }
+
+        default()
+    }
+
+    fn from_args() -> Option<String> {
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            if arg == WORLD_ARG {
+                return args.next();
+            }
+            if let Some(path) = arg
+                .strip_prefix(WORLD_ARG)
+                .and_then(|a| a.strip_prefix('='))
+            {
+                return Some(path.to_owned());
+            }
+        }
+        None
+    }
+}
+
+/// Unloads the current LDtk project and loads the one at the given path instead.
+#[derive(Event, Debug)]
+pub struct ChangeWorld(pub String);
+
+pub(super) fn change_world(
+    mut event_reader: EventReader<ChangeWorld>,
+    mut world_selection: ResMut<WorldSelection>,
+) {
+    let Some(ChangeWorld(path)) = event_reader.read().last() else {
+        return;
+    };
+
+    world_selection.path = path.clone();
+}
+
+pub(super) fn spawn_world(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    world_selection: Res<WorldSelection>,
+    worlds: Query<Entity, With<LdtkProjectHandle>>,
+    mut level_selection: ResMut<LevelSelection>,
+    mut pending_spawn: ResMut<PendingSpawn>,
+) {
+    if !world_selection.is_changed() {
+        return;
+    }
+
+    for world in &worlds {
+        commands.entity(world).despawn_recursive();
+    }
+
+    info!("Loading world {}", world_selection.path);
+    commands.spawn(LdtkWorldBundle {
+        ldtk_handle: asset_server.load(world_selection.path.clone()).into(),
+        ..default()
+    });
+
+    if !world_selection.is_added() {
+        *level_selection = LevelSelection::index(0);
+    }
+    *pending_spawn = PendingSpawn::Default;
+}
+
+pub(super) fn report_world_load_failure(
+    mut event_reader: EventReader<AssetLoadFailedEvent<LdtkProject>>,
+) {
+    for event in event_reader.read() {
+        error!(
+            "Failed to load world `{}`: {}. Pass `{WORLD_ARG} <path>`, set `{WORLD_ENV_VAR}` or edit `{WORLD_CONFIG_PATH}` to choose another project.",
+            event.path, event.error
+        );
+    }
+}
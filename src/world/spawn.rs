@@ -0,0 +1,311 @@
+use avian2d::prelude::LinearVelocity;
+use bevy::{
+    asset::Assets,
+    log::warn,
+    math::{IVec2, Vec2},
+    prelude::{
+        Bundle, Component, Entity, EventReader, EventWriter, GlobalTransform, Local, Parent, Query,
+        Res, ResMut, Resource, Single, Transform, With,
+    },
+    utils::HashSet,
+};
+use bevy_ecs_ldtk::{
+    assets::LdtkProject, prelude::LdtkFields, EntityInstance, LdtkEntity, LdtkProjectHandle,
+    LevelEvent, LevelIid, LevelSelection,
+};
+
+use super::{
+    death::RespawnPoint,
+    level::{on_level_layer, selected_level_entity},
+    level_settings::LevelSettings,
+};
+use crate::{
+    impl_entity,
+    player::{Player, SyncCameraWithPlayer, PLAYER_DIM},
+};
+
+/// A named place the player can enter a level at, e.g. one per door.
+#[derive(Component, Debug, Default)]
+pub struct SpawnPoint {
+    pub name: String,
+}
+
+impl SpawnPoint {
+    fn from_entity_instance(entity_instance: &EntityInstance) -> Self {
+        Self {
+            name: entity_instance
+                .get_string_field("Name")
+                .cloned()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Default, Bundle, LdtkEntity)]
+pub(super) struct SpawnPointBundle {
+    #[with(SpawnPoint::from_entity_instance)]
+    spawn_point: SpawnPoint,
+}
+
+impl_entity!(SpawnPointBundle | "SpawnPoint");
+
+/// Where the player should be placed once the selected level has finished spawning.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq)]
+pub enum PendingSpawn {
+    #[default]
+    None,
+    /// The level's `Spawnpoint` field, or its first [`SpawnPoint`] if it has none.
+    Default,
+    /// The [`SpawnPoint`] with this name, falling back to [`PendingSpawn::Default`].
+    Named(String),
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn spawn_player(
+    mut level_events: EventReader<LevelEvent>,
+    mut pending: ResMut<PendingSpawn>,
+    level_selection: Res<LevelSelection>,
+    level_settings: Res<LevelSettings>,
+    handle: Single<&LdtkProjectHandle>,
+    projects: Res<Assets<LdtkProject>>,
+    levels: Query<(Entity, &LevelIid, &GlobalTransform)>,
+    spawn_points: Query<(&SpawnPoint, &GlobalTransform, &Parent)>,
+    layers: Query<&Parent>,
+    mut player: Query<(&mut Transform, &mut LinearVelocity), With<Player>>,
+    mut respawn_point: ResMut<RespawnPoint>,
+    mut sync_camera: EventWriter<SyncCameraWithPlayer>,
+    mut transformed: Local<HashSet<LevelIid>>,
+) {
+    // neighbours are spawned and placed before they're selected, so their events are long gone by then.
+    for event in level_events.read() {
+        match event {
+            LevelEvent::Transformed(iid) => {
+                transformed.insert(iid.clone());
+            }
+            LevelEvent::SpawnTriggered(iid) | LevelEvent::Despawned(iid) => {
+                transformed.remove(iid);
+            }
+            LevelEvent::Spawned(_) => (),
+        }
+    }
+
+    if *pending == PendingSpawn::None {
+        return;
+    }
+
+    let Some(project) = projects.get(&handle.handle) else {
+        return;
+    };
+    let Some((level, level_entity, level_transform)) =
+        selected_level_entity(project, &level_selection, &levels)
+    else {
+        return;
+    };
+    // the selected level is ready once it has been placed in the world, however long ago.
+    if !transformed.iter().any(|iid| *iid.get() == level.iid) {
+        return;
+    }
+
+    let spawn_point = |name: Option<&str>| {
+        spawn_points
+            .iter()
+            .filter(|(.., layer)| on_level_layer(layer, level_entity, &layers))
+            .find(|(spawn_point, ..)| name.is_none_or(|name| spawn_point.name == name))
+            .map(|(_, transform, _)| transform.translation().truncate())
+    };
+
+    let grid_size = project.json_data().default_grid_size as f32;
+    let level_field = || {
+        let point = parse_grid_point(level_settings.spawnpoint.as_deref()?)?;
+        Some(
+            level_transform.translation().truncate()
+                + Vec2::new(
+                    (point.x as f32 + 0.5) * grid_size,
+                    level.px_hei as f32 - (point.y as f32 + 0.5) * grid_size,
+                ),
+        )
+    };
+
+    let named = match &*pending {
+        PendingSpawn::Named(name) => {
+            let position = spawn_point(Some(name.as_str()));
+            if position.is_none() {
+                warn!("Level `{}` has no spawn point `{name}`", level.identifier);
+            }
+            position
+        }
+        _ => None,
+    };
+
+    let Some(position) = named.or_else(level_field).or_else(|| spawn_point(None)) else {
+        warn!("Level `{}` has no spawn point", level.identifier);
+        *pending = PendingSpawn::None;
+        return;
+    };
+
+    let Ok((mut transform, mut velocity)) = player.get_single_mut() else {
+        return;
+    };
+
+    let position = player_position_at(position, grid_size);
+    transform.translation.x = position.x;
+    transform.translation.y = position.y;
+    *velocity = LinearVelocity::ZERO;
+
+    // entering a level forgets the previous level's checkpoints.
+    *respawn_point = RespawnPoint {
+        position: Some(position),
+        checkpoint: None,
+    };
+    *pending = PendingSpawn::None;
+    sync_camera.send_default();
+}
+
+/// Where the player stands when placed on the grid cell centred at `cell`.
+pub(super) fn player_position_at(cell: Vec2, grid_size: f32) -> Vec2 {
+    // spawn points mark a cell, the player is taller than one and would start inside the floor.
+    cell + Vec2::Y * (PLAYER_DIM.1 - grid_size) / 2.
+}
+
+/// Parses the `x;y` grid coordinates LDtk level fields store spawn points as.
+fn parse_grid_point(s: &str) -> Option<IVec2> {
+    let (x, y) = s.split_once(';')?;
+    Some(IVec2::new(x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::path::Path;
+
+    use avian2d::prelude::LinearVelocity;
+    use bevy::{
+        app::{App, Update},
+        asset::{
+            io::{
+                memory::{Dir, MemoryAssetReader},
+                AssetSource, AssetSourceId,
+            },
+            AssetApp, AssetPlugin, AssetServer, LoadState,
+        },
+        core::TaskPoolPlugin,
+        prelude::{GlobalTransform, Transform},
+    };
+    use bevy_ecs_ldtk::{
+        assets::LdtkAssetPlugin,
+        ldtk::{LdtkJson, Level},
+        LdtkProjectHandle, LevelEvent, LevelIid, LevelSelection,
+    };
+
+    use super::{spawn_player, PendingSpawn};
+    use crate::{
+        player::{Player, SyncCameraWithPlayer},
+        world::{
+            death::RespawnPoint,
+            level_settings::{LevelSettings, Spawnpoint},
+        },
+    };
+
+    pub(crate) fn level_iid(i: usize) -> LevelIid {
+        LevelIid::new(format!("level-{i}"))
+    }
+
+    /// An app with a project of two 190 pixel square levels side by side, loaded the way
+    /// bevy_ecs_ldtk loads project files, and the level entities it would spawn for them.
+    pub(crate) fn app_with_levels() -> App {
+        let level = |i: usize| Level {
+            iid: level_iid(i).get().clone(),
+            identifier: format!("Level_{i}"),
+            px_wid: 190,
+            px_hei: 190,
+            world_x: 190 * i as i32,
+            layer_instances: Some(Vec::new()),
+            ..Default::default()
+        };
+        let json = LdtkJson {
+            default_grid_size: 19,
+            levels: vec![level(0), level(1)],
+            ..Default::default()
+        };
+
+        let dir = Dir::default();
+        dir.insert_asset(
+            Path::new("test.ldtk"),
+            serde_json::to_vec(&json).expect("project should serialize"),
+        );
+        let mut app = App::new();
+        app.register_asset_source(
+            AssetSourceId::Default,
+            AssetSource::build()
+                .with_reader(move || Box::new(MemoryAssetReader { root: dir.clone() })),
+        )
+        .add_plugins((
+            TaskPoolPlugin::default(),
+            AssetPlugin::default(),
+            LdtkAssetPlugin,
+        ));
+
+        let handle = app.world().resource::<AssetServer>().load("test.ldtk");
+        // projects are loaded on another thread.
+        loop {
+            match app.world().resource::<AssetServer>().load_state(&handle) {
+                LoadState::Loaded => break,
+                LoadState::Failed(error) => panic!("project should load: {error}"),
+                _ => app.update(),
+            }
+        }
+
+        app.world_mut().spawn(LdtkProjectHandle { handle });
+        for i in 0..2 {
+            app.world_mut().spawn((
+                level_iid(i),
+                GlobalTransform::from_xyz(190. * i as f32, 0., 0.),
+            ));
+        }
+        app
+    }
+
+    #[test]
+    fn player_spawns_in_preloaded_neighbour() {
+        let mut app = app_with_levels();
+        app.add_event::<LevelEvent>()
+            .add_event::<SyncCameraWithPlayer>()
+            .insert_resource(PendingSpawn::Default)
+            .insert_resource(LevelSelection::Identifier("Level_0".into()))
+            .insert_resource(LevelSettings {
+                spawnpoint: Spawnpoint(Some("2;3".into())),
+                ..Default::default()
+            })
+            .init_resource::<RespawnPoint>()
+            .add_systems(Update, spawn_player);
+
+        let player = app
+            .world_mut()
+            .spawn((Player, Transform::default(), LinearVelocity::default()))
+            .id();
+
+        // the first level loads along with its neighbour.
+        app.world_mut()
+            .send_event(LevelEvent::Transformed(level_iid(0)));
+        app.world_mut()
+            .send_event(LevelEvent::Transformed(level_iid(1)));
+        app.update();
+        assert_eq!(*app.world().resource::<PendingSpawn>(), PendingSpawn::None);
+
+        // moving on to the neighbour doesn't spawn or transform anything.
+        *app.world_mut().resource_mut::<LevelSelection>() =
+            LevelSelection::Identifier("Level_1".into());
+        *app.world_mut().resource_mut::<PendingSpawn>() = PendingSpawn::Default;
+        app.update();
+        app.update();
+
+        assert_eq!(*app.world().resource::<PendingSpawn>(), PendingSpawn::None);
+        let position = app
+            .world()
+            .get::<Transform>(player)
+            .unwrap()
+            .translation
+            .truncate();
+        assert_eq!(position.x, 190. + 2.5 * 19.);
+        assert!(position.y > 0. && position.y < 190., "{position}");
+    }
+}
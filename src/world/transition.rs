@@ -0,0 +1,186 @@
+use bevy::{
+    log::warn,
+    prelude::{NextState, Res, ResMut, Resource, States},
+    time::{Time, Timer},
+    utils::HashMap,
+};
+use bevy_ecs_ldtk::LevelSelection;
+
+use super::spawn::PendingSpawn;
+use crate::{render::fade::ScreenFade, utils::phase_timer};
+
+/// Where a level change is at. Player input is ignored outside of [`LevelTransition::Playing`].
+#[derive(States, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LevelTransition {
+    #[default]
+    Playing,
+    /// Covering up the old level, still selected.
+    FadingOut,
+    /// The new level is selected and spawning, the player gets placed once it's done.
+    Loading,
+    /// Revealing the new level.
+    FadingIn,
+}
+
+/// The [`ChangeLevel`](super::level::ChangeLevel) variant a transition was started by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeLevelKind {
+    Level,
+    LevelAt,
+    ByIdentifier,
+    ByIid,
+    Next,
+    NextInWorld,
+    Previous,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionEffect {
+    /// Fade the screen to black and back through [`ScreenFade`].
+    Fade,
+    /// Draw nothing, leaving it to systems reading [`ActiveTransition`] in the transition's states.
+    Custom,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TransitionStyle {
+    pub effect: TransitionEffect,
+    /// Seconds spent in [`LevelTransition::FadingOut`].
+    pub fade_out: f32,
+    /// Seconds spent in [`LevelTransition::FadingIn`].
+    pub fade_in: f32,
+}
+
+impl Default for TransitionStyle {
+    fn default() -> Self {
+        Self {
+            effect: TransitionEffect::Fade,
+            fade_out: 0.35,
+            fade_in: 0.35,
+        }
+    }
+}
+
+/// Picks the [`TransitionStyle`] for every kind of level change.
+#[derive(Resource, Debug, Clone)]
+pub struct LevelTransitionConfig {
+    pub styles: HashMap<ChangeLevelKind, TransitionStyle>,
+    pub default_style: TransitionStyle,
+    /// Seconds to wait for the new level before fading in regardless.
+    pub loading_timeout: f32,
+}
+
+impl Default for LevelTransitionConfig {
+    fn default() -> Self {
+        Self {
+            styles: HashMap::default(),
+            default_style: TransitionStyle::default(),
+            loading_timeout: 5.,
+        }
+    }
+}
+
+impl LevelTransitionConfig {
+    pub fn style(&self, kind: ChangeLevelKind) -> TransitionStyle {
+        self.styles
+            .get(&kind)
+            .copied()
+            .unwrap_or(self.default_style)
+    }
+}
+
+/// The level change in progress, inserted when it starts.
+#[derive(Resource, Debug)]
+pub struct ActiveTransition {
+    pub kind: ChangeLevelKind,
+    pub style: TransitionStyle,
+    pub level: LevelSelection,
+    pub spawn: PendingSpawn,
+    /// Times the current state.
+    pub timer: Timer,
+}
+
+impl ActiveTransition {
+    pub(super) fn new(
+        kind: ChangeLevelKind,
+        style: TransitionStyle,
+        level: LevelSelection,
+        spawn: PendingSpawn,
+    ) -> Self {
+        Self {
+            kind,
+            style,
+            level,
+            spawn,
+            timer: phase_timer(style.fade_out),
+        }
+    }
+
+    /// How far along the current state is, from `0.` to `1.`.
+    pub fn progress(&self) -> f32 {
+        self.timer.fraction()
+    }
+}
+
+pub(super) fn fade_out(
+    mut transition: ResMut<ActiveTransition>,
+    time: Res<Time>,
+    config: Res<LevelTransitionConfig>,
+    mut fade: ResMut<ScreenFade>,
+    mut level_selection: ResMut<LevelSelection>,
+    mut pending_spawn: ResMut<PendingSpawn>,
+    mut next_state: ResMut<NextState<LevelTransition>>,
+) {
+    transition.timer.tick(time.delta());
+    if transition.style.effect == TransitionEffect::Fade {
+        fade.0 = transition.progress();
+    }
+
+    if !transition.timer.finished() {
+        return;
+    }
+
+    *level_selection = transition.level.clone();
+    *pending_spawn = transition.spawn.clone();
+    transition.timer = phase_timer(config.loading_timeout);
+    next_state.set(LevelTransition::Loading);
+}
+
+pub(super) fn wait_for_level(
+    mut transition: ResMut<ActiveTransition>,
+    time: Res<Time>,
+    pending_spawn: Res<PendingSpawn>,
+    mut next_state: ResMut<NextState<LevelTransition>>,
+) {
+    transition.timer.tick(time.delta());
+
+    // `spawn_player` clears the pending spawn once the player stands in the new level.
+    if *pending_spawn != PendingSpawn::None {
+        if !transition.timer.finished() {
+            return;
+        }
+        warn!(
+            "Level {:?} took too long to spawn, showing it anyway",
+            transition.level
+        );
+    }
+
+    transition.timer = phase_timer(transition.style.fade_in);
+    next_state.set(LevelTransition::FadingIn);
+}
+
+pub(super) fn fade_in(
+    mut transition: ResMut<ActiveTransition>,
+    time: Res<Time>,
+    mut fade: ResMut<ScreenFade>,
+    mut next_state: ResMut<NextState<LevelTransition>>,
+) {
+    transition.timer.tick(time.delta());
+    if transition.style.effect == TransitionEffect::Fade {
+        fade.0 = 1. - transition.progress();
+    }
+
+    if transition.timer.finished() {
+        next_state.set(LevelTransition::Playing);
+    }
+}
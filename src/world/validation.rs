@@ -0,0 +1,222 @@
+use std::{any::type_name, collections::BTreeSet, fmt};
+
+use bevy::{
+    asset::{AssetEvent, Assets},
+    log::{error, info},
+    prelude::{EventReader, Res, ResMut, Resource},
+};
+use bevy_ecs_ldtk::assets::LdtkProject;
+
+use super::{level_settings::LevelSettings, Entity, IntCell};
+
+/// Every LDtk entity and IntGrid value bound to a Rust bundle through [`LdtkAppTraitExt`](crate::utils::LdtkAppTraitExt).
+#[derive(Resource, Default, Debug)]
+pub struct LdtkRegistry {
+    /// `(identifier, bundle type)`
+    pub entities: Vec<(&'static str, &'static str)>,
+    /// `(layer, value, bundle type)`, where no layer means any layer.
+    pub int_cells: Vec<(Option<&'static str>, i32, &'static str)>,
+}
+
+impl LdtkRegistry {
+    pub fn record_entity<B: Entity>(&mut self) {
+        self.entities.push((B::IDENTIFIER, type_name::<B>()));
+    }
+
+    pub fn record_int_cell<B: IntCell>(&mut self) {
+        self.int_cells
+            .push((B::LAYER, B::INTCELL_ID, type_name::<B>()));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LdtkValidationIssue {
+    /// A bundle is registered for an entity identifier the project doesn't define.
+    MissingEntity {
+        identifier: &'static str,
+        bundle: &'static str,
+    },
+    /// The project defines an entity no bundle is registered for.
+    UnregisteredEntity { identifier: String },
+    /// A bundle is registered for an IntGrid value no layer defines, or not the layer it's bound to.
+    MissingIntCell {
+        layer: Option<&'static str>,
+        value: i32,
+        bundle: &'static str,
+    },
+    /// An IntGrid layer defines a value no bundle is registered for.
+    UnregisteredIntCell { layer: String, value: i32 },
+    /// [`LevelSettings`] reads a required level field the project doesn't define.
+    MissingLevelField {
+        identifier: &'static str,
+        expected: &'static str,
+    },
+    /// A level field exists, but with a different type than [`LevelSettings`] reads.
+    LevelFieldTypeMismatch {
+        identifier: &'static str,
+        expected: &'static str,
+        found: String,
+    },
+}
+
+impl fmt::Display for LdtkValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingEntity { identifier, bundle } => write!(
+                f,
+                "entity `{identifier}` is registered by `{bundle}` but not defined in the project"
+            ),
+            Self::UnregisteredEntity { identifier } => {
+                write!(f, "entity `{identifier}` has no registered bundle")
+            }
+            Self::MissingIntCell {
+                layer: Some(layer),
+                value,
+                bundle,
+            } => write!(
+                f,
+                "IntGrid value {value} on layer `{layer}` is registered by `{bundle}` but the layer doesn't define it"
+            ),
+            Self::MissingIntCell {
+                layer: None,
+                value,
+                bundle,
+            } => write!(
+                f,
+                "IntGrid value {value} is registered by `{bundle}` but no layer defines it"
+            ),
+            Self::UnregisteredIntCell { layer, value } => write!(
+                f,
+                "IntGrid value {value} on layer `{layer}` has no registered bundle"
+            ),
+            Self::MissingLevelField {
+                identifier,
+                expected,
+            } => write!(
+                f,
+                "level field `{identifier}` ({expected}) is not defined in the project"
+            ),
+            Self::LevelFieldTypeMismatch {
+                identifier,
+                expected,
+                found,
+            } => write!(
+                f,
+                "level field `{identifier}` should be {expected}, but the project declares {found}"
+            ),
+        }
+    }
+}
+
+/// The outcome of checking the last loaded LDtk project against [`LdtkRegistry`] and [`LevelSettings`].
+#[derive(Resource, Default, Debug)]
+pub struct LdtkValidationReport {
+    pub issues: Vec<LdtkValidationIssue>,
+}
+
+impl LdtkValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    pub fn validate(project: &LdtkProject, registry: &LdtkRegistry) -> Self {
+        let defs = &project.json_data().defs;
+        let mut issues = Vec::new();
+
+        let project_entities: BTreeSet<&str> = defs
+            .entities
+            .iter()
+            .map(|e| e.identifier.as_str())
+            .collect();
+        let registered_entities: BTreeSet<&str> =
+            registry.entities.iter().map(|(id, _)| *id).collect();
+
+        for &(identifier, bundle) in &registry.entities {
+            if !project_entities.contains(identifier) {
+                issues.push(LdtkValidationIssue::MissingEntity { identifier, bundle });
+            }
+        }
+        for identifier in project_entities.difference(&registered_entities) {
+            issues.push(LdtkValidationIssue::UnregisteredEntity {
+                identifier: identifier.to_string(),
+            });
+        }
+
+        for &(layer_id, value, bundle) in &registry.int_cells {
+            let defined = defs
+                .layers
+                .iter()
+                .filter(|layer| layer_id.is_none_or(|id| layer.identifier == id))
+                .any(|layer| layer.int_grid_values.iter().any(|v| v.value == value));
+            if !defined {
+                issues.push(LdtkValidationIssue::MissingIntCell {
+                    layer: layer_id,
+                    value,
+                    bundle,
+                });
+            }
+        }
+        for layer in &defs.layers {
+            for v in &layer.int_grid_values {
+                let registered = registry.int_cells.iter().any(|&(layer_id, value, _)| {
+                    value == v.value && layer_id.is_none_or(|id| layer.identifier == id)
+                });
+                if !registered {
+                    issues.push(LdtkValidationIssue::UnregisteredIntCell {
+                        layer: layer.identifier.clone(),
+                        value: v.value,
+                    });
+                }
+            }
+        }
+
+        for field in LevelSettings::FIELDS {
+            match defs
+                .level_fields
+                .iter()
+                .find(|f| f.identifier == field.identifier)
+            {
+                None if field.optional => (),
+                None => issues.push(LdtkValidationIssue::MissingLevelField {
+                    identifier: field.identifier,
+                    expected: field.field_type,
+                }),
+                Some(def) if def.field_definition_type != field.field_type => {
+                    issues.push(LdtkValidationIssue::LevelFieldTypeMismatch {
+                        identifier: field.identifier,
+                        expected: field.field_type,
+                        found: def.field_definition_type.clone(),
+                    })
+                }
+                Some(_) => (),
+            }
+        }
+
+        Self { issues }
+    }
+}
+
+pub(super) fn validate_project(
+    mut events: EventReader<AssetEvent<LdtkProject>>,
+    projects: Res<Assets<LdtkProject>>,
+    registry: Res<LdtkRegistry>,
+    mut report: ResMut<LdtkValidationReport>,
+) {
+    for event in events.read() {
+        let (AssetEvent::Added { id } | AssetEvent::Modified { id }) = event else {
+            continue;
+        };
+        let Some(project) = projects.get(*id) else {
+            continue;
+        };
+
+        *report = LdtkValidationReport::validate(project, &registry);
+
+        if report.is_valid() {
+            info!("LDtk project matches all registered types.");
+        }
+        for issue in &report.issues {
+            error!("LDtk validation: {issue}");
+        }
+    }
+}